@@ -1,3 +1,4 @@
+#![cfg(feature = "nightly")]
 #![feature(test)]
 
 extern crate geo_types;
@@ -5,7 +6,7 @@ extern crate geohash;
 extern crate test;
 
 use geo_types::Coordinate;
-use geohash::{decode, encode, neighbor, neighbors, Direction};
+use geohash::{decode, encode, encode_batch_simd, neighbor, neighbors, Direction};
 use test::Bencher;
 
 #[bench]
@@ -20,9 +21,37 @@ fn benchmark_encode(b: &mut Bencher) {
     })
 }
 
+fn batch_coordinates() -> Vec<Coordinate<f64>> {
+    (0..1024)
+        .map(|i| Coordinate {
+            x: -180.0 + 0.35 * f64::from(i),
+            y: -90.0 + 0.17 * f64::from(i),
+        })
+        .collect()
+}
+
+#[bench]
+fn benchmark_encode_many(b: &mut Bencher) {
+    let coordinates = batch_coordinates();
+
+    b.iter(|| {
+        coordinates
+            .iter()
+            .map(|&c| encode(c, 12).expect("The Coordinates were not possible"))
+            .collect::<Vec<_>>()
+    })
+}
+
+#[bench]
+fn benchmark_encode_batch_simd(b: &mut Bencher) {
+    let coordinates = batch_coordinates();
+
+    b.iter(|| encode_batch_simd(&coordinates, 12).expect("The Coordinates were not possible"))
+}
+
 #[bench]
 fn benchmark_decode(b: &mut Bencher) {
-    let hash = "9q60y60rhs";
+    let hash = "4d8c0f1817";
 
     b.iter(|| {
         decode(hash).expect("The hashstring was malformed");
@@ -31,7 +60,7 @@ fn benchmark_decode(b: &mut Bencher) {
 
 #[bench]
 fn benchmark_neighbor(b: &mut test::Bencher) {
-    let hash = "9q60y60rhs";
+    let hash = "4d8c0f1817";
 
     b.iter(|| {
         neighbor(hash, Direction::N).expect("The hashstring was malformed");
@@ -40,7 +69,16 @@ fn benchmark_neighbor(b: &mut test::Bencher) {
 
 #[bench]
 fn benchmark_neighbors(b: &mut test::Bencher) {
-    let hash = "9q60y60rhs";
+    let hash = "4d8c0f1817";
+
+    b.iter(|| {
+        neighbors(hash).expect("The hashstring was malformed");
+    })
+}
+
+#[bench]
+fn benchmark_neighbors_short(b: &mut test::Bencher) {
+    let hash = "4d8";
 
     b.iter(|| {
         neighbors(hash).expect("The hashstring was malformed");
@@ -0,0 +1,127 @@
+#[cfg(feature = "simd")]
+use std::simd::cmp::SimdPartialOrd;
+#[cfg(feature = "simd")]
+use std::simd::{f64x4, Select};
+
+use crate::core::{encode_unchecked, BASE32_CODES};
+use crate::{Coordinate, GeohashError};
+
+use failure::Error;
+
+/// Number of coordinates encoded side by side.
+const LANES: usize = 4;
+
+/// Encode many coordinates to geohashes with length `len`, several at a time,
+/// using `std::simd` with the `simd` feature and a scalar fallback without it.
+///
+/// The cells of `LANES` coordinates are bisected together, each step
+/// comparing all of them against their midpoints at once. The `simd`
+/// feature needs a nightly toolchain; without it the function is still
+/// available, and the comparisons run on plain arrays, which the compiler
+/// may still vectorize. Either way the bisection is the one `encode` does,
+/// so the hashes are identical to calling it on every coordinate. The last
+/// `coords.len() % 4` coordinates are encoded one by one.
+///
+/// All coordinates are checked before any is encoded, and the first one out
+/// of range is reported as `GeohashError::InvalidAtIndex`. A `len` of zero
+/// is rejected.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coords = vec![geohash::Coordinate { x: -120.6623, y: 35.3003 }; 5];
+///
+/// let hashes = geohash::encode_batch_simd(&coords, 5).expect("Invalid coordinate");
+///
+/// assert_eq!(hashes, vec!["4d8c0"; 5]);
+/// ```
+pub fn encode_batch_simd(coords: &[Coordinate<f64>], len: usize) -> Result<Vec<String>, Error> {
+    if len == 0 {
+        bail!(GeohashError::InvalidLength { len });
+    }
+    for (index, &c) in coords.iter().enumerate() {
+        if c.x < -180f64 || c.x > 180f64 || c.y < -90f64 || c.y > 90f64 {
+            bail!(GeohashError::InvalidAtIndex {
+                index,
+                error: GeohashError::InvalidCoordinateRange { c }.into(),
+            });
+        }
+    }
+
+    let mut out = Vec::with_capacity(coords.len());
+    let mut chunks = coords.chunks_exact(LANES);
+    for chunk in &mut chunks {
+        let mut xs = [0f64; LANES];
+        let mut ys = [0f64; LANES];
+        for (lane, c) in chunk.iter().enumerate() {
+            xs[lane] = c.x;
+            ys[lane] = c.y;
+        }
+        out.extend(encode_lanes(xs, ys, len));
+    }
+    out.extend(chunks.remainder().iter().map(|&c| encode_unchecked(c, len)));
+    Ok(out)
+}
+
+/// Encode `LANES` coordinates, given as their longitudes and latitudes.
+fn encode_lanes(xs: [f64; LANES], ys: [f64; LANES], len: usize) -> [String; LANES] {
+    let mut min_lon = [-180f64; LANES];
+    let mut max_lon = [180f64; LANES];
+    let mut min_lat = [-90f64; LANES];
+    let mut max_lat = [90f64; LANES];
+    let mut out: [String; LANES] = Default::default();
+    for hash in out.iter_mut() {
+        hash.reserve(len);
+    }
+
+    for _ in 0..len {
+        let mut hash_values = [0usize; LANES];
+        for bit in 0..4 {
+            let upper = if bit % 2 == 0 {
+                split(&xs, &mut min_lon, &mut max_lon)
+            } else {
+                split(&ys, &mut min_lat, &mut max_lat)
+            };
+            for (lane, hash_value) in hash_values.iter_mut().enumerate() {
+                *hash_value = (*hash_value << 1) | ((upper >> lane) & 1) as usize;
+            }
+        }
+        for (hash, &hash_value) in out.iter_mut().zip(&hash_values) {
+            hash.push(BASE32_CODES[hash_value]);
+        }
+    }
+    out
+}
+
+/// Halve every lane's interval `[min, max]` around its value in `vs`,
+/// keeping the upper half when the value lies strictly above the midpoint as
+/// `encode` does. Returns a bitmask of the lanes that kept the upper half.
+#[cfg(feature = "simd")]
+fn split(vs: &[f64; LANES], min: &mut [f64; LANES], max: &mut [f64; LANES]) -> u64 {
+    let (v, lo, hi) = (
+        f64x4::from_array(*vs),
+        f64x4::from_array(*min),
+        f64x4::from_array(*max),
+    );
+    let mid = (lo + hi) / f64x4::splat(2f64);
+    let upper = v.simd_gt(mid);
+    *min = upper.select(mid, lo).to_array();
+    *max = upper.select(hi, mid).to_array();
+    upper.to_bitmask()
+}
+
+/// Scalar `split`, for builds without the `simd` feature.
+#[cfg(not(feature = "simd"))]
+fn split(vs: &[f64; LANES], min: &mut [f64; LANES], max: &mut [f64; LANES]) -> u64 {
+    let mut upper = 0u64;
+    for lane in 0..LANES {
+        let mid = (min[lane] + max[lane]) / 2f64;
+        if vs[lane] > mid {
+            min[lane] = mid;
+            upper |= 1 << lane;
+        } else {
+            max[lane] = mid;
+        }
+    }
+    upper
+}
@@ -0,0 +1,359 @@
+use crate::core::{encode, hash_value_of_char, BASE32_CODES};
+use crate::{Coordinate, GeohashError};
+
+use failure::Error;
+
+/// Number of bits of position carried by a geohash: four per character.
+///
+/// Standard base32 geohashes carry five bits per character, so the two
+/// formats only reach the same precision at lengths in a 5:4 ratio, e.g. a
+/// length 5 hash here matches a length 4 base32 geohash, both 20 bits. The
+/// characters themselves are not interchangeable.
+///
+/// ### Examples
+///
+/// ```rust
+/// assert_eq!(geohash::bits_of("e7115"), 20);
+/// ```
+pub fn bits_of(hash_str: &str) -> usize {
+    4 * hash_str.chars().count()
+}
+
+/// Express a geohash as a path in the 16-way cell tree.
+///
+/// Each element is the index (`0`–`15`) of the child taken at that level,
+/// starting from the root, so the path has one entry per character.
+///
+/// ### Examples
+///
+/// ```rust
+/// let path = geohash::to_h3_like_path("e71").expect("Invalid hash string");
+///
+/// assert_eq!(path, vec![14, 7, 1]);
+/// ```
+pub fn to_h3_like_path(hash_str: &str) -> Result<Vec<u8>, Error> {
+    hash_str
+        .chars()
+        .map(|c| hash_value_of_char(c).map(|v| v as u8))
+        .collect()
+}
+
+/// Build a geohash from a path of child indices, the inverse of
+/// `to_h3_like_path`.
+///
+/// ### Examples
+///
+/// ```rust
+/// let hash = geohash::from_index_path(&[14, 7, 1]).expect("Invalid path");
+///
+/// assert_eq!(hash, "e71");
+/// ```
+pub fn from_index_path(path: &[u8]) -> Result<String, Error> {
+    let mut out = String::with_capacity(path.len());
+    for &index in path {
+        match BASE32_CODES.get(index as usize) {
+            Some(&code) => out.push(code),
+            None => bail!(GeohashError::InvalidPathIndex { index }),
+        }
+    }
+    Ok(out)
+}
+
+/// Express a geohash as a Bing-style quadkey.
+///
+/// Each character carries two longitude/latitude bit pairs, so the quadkey
+/// has two digits per character. A pair maps to a digit the way Bing numbers
+/// its quadrants, with the longitude bit as the low bit and the *inverted*
+/// latitude bit as the high bit:
+///
+/// | quadrant | lon bit | lat bit | digit |
+/// |----------|---------|---------|-------|
+/// | NW       | 0       | 1       | `0`   |
+/// | NE       | 1       | 1       | `1`   |
+/// | SW       | 0       | 0       | `2`   |
+/// | SE       | 1       | 0       | `3`   |
+///
+/// The quadrants are those of the geohash's own latitude/longitude grid, not
+/// of the Web Mercator tiles Bing uses.
+///
+/// ### Examples
+///
+/// ```rust
+/// let quadkey = geohash::to_quadkey("e7").expect("Invalid hash string");
+///
+/// assert_eq!(quadkey, "1301");
+/// ```
+pub fn to_quadkey(hash_str: &str) -> Result<String, Error> {
+    let mut out = String::with_capacity(hash_str.len() * 2);
+    for c in hash_str.chars() {
+        let hash_value = hash_value_of_char(c)?;
+        for shift in &[2, 0] {
+            let lon_bit = (hash_value >> (shift + 1)) & 1;
+            let lat_bit = (hash_value >> shift) & 1;
+            let digit = lon_bit + 2 * (1 - lat_bit);
+            out.push((b'0' + digit as u8) as char);
+        }
+    }
+    Ok(out)
+}
+
+/// XOR the interleaved bits of two geohashes of equal length.
+///
+/// Both hashes are read as a `4 * len` bit integer, most significant bit
+/// first: the first character fills the top four bits and, within each
+/// character, bits alternate longitude, latitude, longitude, latitude. This
+/// is the same as parsing the hash as a hexadecimal number. The higher the
+/// top set bit of the result, the earlier the two cells part ways in the cell
+/// tree, so it ranks how far apart they are.
+///
+/// Hashes longer than 16 characters do not fit in a `u64` and are rejected.
+///
+/// ### Examples
+///
+/// ```rust
+/// let diff = geohash::bit_diff("e711", "e714").expect("Invalid hash string");
+///
+/// assert_eq!(diff, 0x5);
+/// ```
+pub fn bit_diff(a: &str, b: &str) -> Result<u64, Error> {
+    if a.len() != b.len() {
+        bail!(GeohashError::LengthMismatch {
+            left: a.len(),
+            right: b.len(),
+        });
+    }
+    Ok(to_bits(a)? ^ to_bits(b)?)
+}
+
+/// Split a geohash into the prefix of its first `prefix_len` characters, the
+/// cell holding it at that length, and the suffix locating it within.
+///
+/// Suited to tiered storage: the prefix makes a coarse partition key and the
+/// suffix a row key within the partition. The whole hash is validated, and a
+/// `prefix_len` past its end yields `GeohashError::InvalidLength`; splitting
+/// at either end leaves an empty prefix or suffix.
+///
+/// ### Examples
+///
+/// ```rust
+/// let (prefix, suffix) = geohash::split_at("e71150dc9", 4).expect("Invalid hash string");
+///
+/// assert_eq!((prefix, suffix), ("e711", "50dc9"));
+/// ```
+pub fn split_at(hash_str: &str, prefix_len: usize) -> Result<(&str, &str), Error> {
+    for c in hash_str.chars() {
+        hash_value_of_char(c)?;
+    }
+    if prefix_len > hash_str.len() {
+        bail!(GeohashError::InvalidLength { len: prefix_len });
+    }
+    Ok(hash_str.split_at(prefix_len))
+}
+
+/// Count the leading characters two geohashes agree on.
+///
+/// The count is the length of the deepest cell containing both, so it works
+/// as a coarse proximity score: the larger it is, the closer the two cells.
+/// Hashes of different lengths are fine and letters compare case
+/// insensitively. Both hashes are validated in full, even past the point
+/// where they part ways.
+///
+/// ### Examples
+///
+/// ```rust
+/// let len = geohash::common_prefix_len("e71150", "e7114f").expect("Invalid hash string");
+///
+/// assert_eq!(len, 4);
+/// ```
+pub fn common_prefix_len(a: &str, b: &str) -> Result<usize, Error> {
+    let a = to_h3_like_path(a)?;
+    let b = to_h3_like_path(b)?;
+    Ok(a.iter().zip(&b).take_while(|(x, y)| x == y).count())
+}
+
+/// Encode a coordinate to exactly `bits` interleaved bits, for integer keys
+/// whose width is not a multiple of four.
+///
+/// The bits are those of `encode`, read as in `bit_diff`: longitude first,
+/// most significant bit first, right-aligned in the result. Only whole
+/// characters make it into the string form, so a key of `bits` bits is the
+/// length `ceil(bits / 4)` geohash with the bits of its last, partial
+/// character past `bits` dropped, e.g. a 45-bit key is a 12 character hash
+/// shifted right by 3. `bits` must be between 1 and 64.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coord = geohash::Coordinate { x: 112.5584, y: 37.8324 };
+///
+/// assert_eq!(geohash::encode_bits(coord, 16).unwrap(), 0xe711);
+/// assert_eq!(geohash::encode_bits(coord, 14).unwrap(), 0xe711 >> 2);
+/// ```
+pub fn encode_bits(c: Coordinate<f64>, bits: usize) -> Result<u64, Error> {
+    if bits == 0 || bits > 64 {
+        bail!(GeohashError::InvalidBitDepth { bits });
+    }
+    let len = bits.div_ceil(4);
+    Ok(to_bits(&encode(c, len)?)? >> (4 * len - bits))
+}
+
+/// Find the inclusive range of `bits`-bit integer keys lying under a geohash
+/// prefix.
+///
+/// Keys use the layout of `bit_diff`, the hash read as a hexadecimal number,
+/// left-aligned so the prefix fills the top `4 * len` of the `bits` bits.
+/// Every longer hash starting with `hash_str`, encoded the same way at the
+/// same depth, falls within the range, so a prefix search becomes a single
+/// range scan. `bits` must be at most 64 and at least `4 * len`.
+///
+/// ### Examples
+///
+/// ```rust
+/// let range = geohash::int_range("e71", 16).expect("Invalid hash string");
+///
+/// assert_eq!(range, (0xe710, 0xe71f));
+/// ```
+pub fn int_range(hash_str: &str, bits: usize) -> Result<(u64, u64), Error> {
+    if bits > 64 || bits < bits_of(hash_str) {
+        bail!(GeohashError::InvalidBitDepth { bits });
+    }
+    let prefix = to_bits(hash_str)?;
+    let free = bits - bits_of(hash_str);
+    if free == 64 {
+        return Ok((0, u64::MAX));
+    }
+    let min = prefix << free;
+    Ok((min, min | ((1u64 << free) - 1)))
+}
+
+/// Position of a geohash's cell along the Hilbert curve through all cells of
+/// its length, counted from the south-west corner.
+///
+/// Geohashes sort in Z-order, whose curve jumps across the map at every
+/// quadrant boundary. Consecutive Hilbert indices always belong to cells
+/// sharing an edge, so ranges of them stay more compact. The curve starts in
+/// the south-west cell and ends in the south-east one. Hashes longer than 16
+/// characters are rejected.
+///
+/// ### Examples
+///
+/// ```rust
+/// assert_eq!(geohash::hilbert_index("0").unwrap(), 0);
+/// assert_eq!(geohash::hilbert_index("2").unwrap(), 1);
+/// assert_eq!(geohash::hilbert_index("a").unwrap(), 15);
+/// ```
+pub fn hilbert_index(hash_str: &str) -> Result<u64, Error> {
+    let (mut x, mut y) = grid_from_hash(hash_str)?;
+    let n = 1u64 << (2 * hash_str.len());
+    let mut d = 0u64;
+    let mut s = n / 2;
+    while s > 0 {
+        let rx = u64::from(x & s > 0);
+        let ry = u64::from(y & s > 0);
+        d += s * s * ((3 * rx) ^ ry);
+        // Rotate the quadrant so the sub-curve starts in its south-west cell
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    Ok(d)
+}
+
+/// Split a geohash into the column and row of its cell in the grid of all
+/// cells of its length.
+///
+/// Columns count from longitude `-180` eastwards and rows from latitude
+/// `-90` northwards. Every character holds two longitude and two latitude
+/// bits, so unlike base32 geohashes both axes always get the same width:
+/// `2 * len` bits, and indices range over `0..4^len`. A `u32` holds up to 16
+/// characters; longer hashes are rejected.
+///
+/// ### Examples
+///
+/// ```rust
+/// assert_eq!(geohash::grid_coords("0").unwrap(), (0, 0));
+/// assert_eq!(geohash::grid_coords("f").unwrap(), (3, 3));
+/// assert_eq!(geohash::grid_coords("e7").unwrap(), (13, 11));
+/// ```
+pub fn grid_coords(hash_str: &str) -> Result<(u32, u32), Error> {
+    let (col, row) = grid_from_hash(hash_str)?;
+    Ok((col as u32, row as u32))
+}
+
+/// Build the length `len` geohash of the cell in column `col` and row `row`,
+/// the inverse of `grid_coords`.
+///
+/// Both indices must fit in the `2 * len` bits each axis gets at that length,
+/// i.e. be below `4^len`. Lengths of zero or above 16 are rejected.
+///
+/// ### Examples
+///
+/// ```rust
+/// assert_eq!(geohash::from_grid_coords(13, 11, 2).unwrap(), "e7");
+/// assert!(geohash::from_grid_coords(16, 0, 2).is_err());
+/// ```
+pub fn from_grid_coords(col: u32, row: u32, len: usize) -> Result<String, Error> {
+    if len == 0 || len > 16 {
+        bail!(GeohashError::InvalidLength { len });
+    }
+    let cells_per_axis = 1u64 << (2 * len);
+    if u64::from(col) >= cells_per_axis || u64::from(row) >= cells_per_axis {
+        bail!(GeohashError::InvalidGridCoords { col, row, len });
+    }
+    Ok(hash_from_grid(u64::from(col), u64::from(row), len))
+}
+
+/// Read a geohash as a hexadecimal number, see `bit_diff`. Hashes longer
+/// than 16 characters are rejected.
+pub(crate) fn to_bits(hash_str: &str) -> Result<u64, Error> {
+    if hash_str.len() > 16 {
+        bail!(GeohashError::InvalidLength {
+            len: hash_str.len(),
+        });
+    }
+    let mut bits = 0u64;
+    for c in hash_str.chars() {
+        bits = (bits << 4) | hash_value_of_char(c)? as u64;
+    }
+    Ok(bits)
+}
+
+/// Build the length `len` geohash of the cell in column `col` (counted from
+/// longitude `-180`) and row `row` (counted from latitude `-90`).
+pub(crate) fn hash_from_grid(col: u64, row: u64, len: usize) -> String {
+    let mut out = String::with_capacity(len);
+    for i in (0..len).rev() {
+        let lon = (col >> (2 * i)) & 3;
+        let lat = (row >> (2 * i)) & 3;
+        let hash_value = (lon >> 1) << 3 | (lat >> 1) << 2 | (lon & 1) << 1 | (lat & 1);
+        out.push(BASE32_CODES[hash_value as usize]);
+    }
+    out
+}
+
+/// Number of leading characters two geohashes have in common.
+pub(crate) fn shared_prefix_len(a: &str, b: &str) -> usize {
+    a.bytes().zip(b.bytes()).take_while(|(x, y)| x == y).count()
+}
+
+/// Column and row of a geohash's cell, the inverse of `hash_from_grid`.
+/// Hashes longer than 16 characters are rejected.
+pub(crate) fn grid_from_hash(hash_str: &str) -> Result<(u64, u64), Error> {
+    if hash_str.len() > 16 {
+        bail!(GeohashError::InvalidLength {
+            len: hash_str.len(),
+        });
+    }
+    let (mut col, mut row) = (0u64, 0u64);
+    for c in hash_str.chars() {
+        let hash_value = hash_value_of_char(c)? as u64;
+        col = (col << 2) | (hash_value >> 3) << 1 | ((hash_value >> 1) & 1);
+        row = (row << 2) | ((hash_value >> 2) & 1) << 1 | (hash_value & 1);
+    }
+    Ok((col, row))
+}
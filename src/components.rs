@@ -0,0 +1,90 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::core::{decode, neighbor_of};
+use crate::Direction;
+
+use failure::Error;
+
+/// Which cells count as touching when grouping cells into regions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Cells sharing an edge: the `N`, `E`, `S` and `W` neighbors.
+    Four,
+    /// Cells sharing an edge or a corner: all 8 neighbors.
+    Eight,
+}
+
+impl Connectivity {
+    fn directions(self) -> &'static [Direction] {
+        match self {
+            Connectivity::Four => &[Direction::N, Direction::E, Direction::S, Direction::W],
+            Connectivity::Eight => &Direction::ALL,
+        }
+    }
+}
+
+/// Group cells into connected regions.
+///
+/// Two cells are connected when one is a neighbor of the other under the
+/// given `connectivity`, and regions are the transitive closure of that.
+/// Neighbors are found at the same length, so only cells of equal length can
+/// connect. Each region is sorted, and regions come in the order of their
+/// first cell in `cells`. Letters are compared case insensitively and cells
+/// are returned in lowercase. Duplicate cells are merged, and any invalid
+/// cell is an error.
+///
+/// ### Examples
+///
+/// ```rust
+/// use geohash::Connectivity;
+///
+/// // "0" and "3" only touch at a corner
+/// let cells = ["0", "3", "1"];
+///
+/// let four = geohash::connected_components(&cells[..2], Connectivity::Four).unwrap();
+/// let eight = geohash::connected_components(&cells[..2], Connectivity::Eight).unwrap();
+/// let bridged = geohash::connected_components(&cells, Connectivity::Four).unwrap();
+///
+/// assert_eq!(four, vec![vec!["0"], vec!["3"]]);
+/// assert_eq!(eight, vec![vec!["0", "3"]]);
+/// assert_eq!(bridged, vec![vec!["0", "1", "3"]]);
+/// ```
+pub fn connected_components(
+    cells: &[&str],
+    connectivity: Connectivity,
+) -> Result<Vec<Vec<String>>, Error> {
+    for &cell in cells {
+        decode(cell)?;
+    }
+    // neighbors come back in lowercase
+    let cells: Vec<String> = cells.iter().map(|cell| cell.to_lowercase()).collect();
+    let mut unvisited: HashSet<&str> = cells.iter().map(|cell| cell.as_str()).collect();
+
+    let mut components = Vec::new();
+    for start in &cells {
+        let start = start.as_str();
+        if !unvisited.remove(start) {
+            continue;
+        }
+        let mut component = vec![start.to_owned()];
+        let mut queue = VecDeque::new();
+        queue.push_back(start.to_owned());
+        while let Some(cell) = queue.pop_front() {
+            let decoded = decode(&cell)?;
+            for &direction in connectivity.directions() {
+                let neighbor = match neighbor_of(decoded, cell.len(), direction) {
+                    Ok(neighbor) => neighbor,
+                    // past a pole
+                    Err(_) => continue,
+                };
+                if unvisited.remove(neighbor.as_str()) {
+                    component.push(neighbor.clone());
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        component.sort();
+        components.push(component);
+    }
+    Ok(components)
+}
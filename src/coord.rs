@@ -0,0 +1,220 @@
+#[cfg(feature = "rand")]
+use crate::core::decode_bbox;
+use crate::core::{decode, encode};
+use crate::{Coordinate, GeohashError};
+
+use failure::Error;
+#[cfg(feature = "rand")]
+use rand::Rng;
+
+/// Bring a coordinate into the range `encode` accepts.
+///
+/// The longitude is wrapped into `[-180, 180)` as
+/// `(x + 180).rem_euclid(360) - 180`, so `180` becomes `-180` and `190`
+/// becomes `-170`. The latitude is clamped into `[-90, 90]`, since wrapping
+/// it over a pole would also have to move the longitude.
+///
+/// ### Examples
+///
+/// ```rust
+/// let c = geohash::normalize(geohash::Coordinate { x: 190.0, y: -100.0 });
+///
+/// assert_eq!(c, geohash::Coordinate { x: -170.0, y: -90.0 });
+/// ```
+pub fn normalize(c: Coordinate<f64>) -> Coordinate<f64> {
+    Coordinate {
+        x: (c.x + 180f64).rem_euclid(360f64) - 180f64,
+        y: c.y.clamp(-90f64, 90f64),
+    }
+}
+
+/// Encode the integer-degree grid point `(lon, lat)` to a geohash with length
+/// `len`.
+///
+/// This is `encode` restricted to whole degrees, as used to key gridded data.
+/// Grid points lie on cell edges at every length, so they are assigned like
+/// any edge point, to the cell south-west of them. Longitudes outside
+/// `[-180, 180]` and latitudes outside `[-90, 90]` are rejected.
+///
+/// ### Examples
+///
+/// ```rust
+/// let hash = geohash::grid_point_hash(112, 37, 6).expect("Invalid grid point");
+///
+/// assert_eq!(hash, geohash::encode(geohash::Coordinate { x: 112.0, y: 37.0 }, 6).unwrap());
+/// ```
+pub fn grid_point_hash(lon: i32, lat: i32, len: usize) -> Result<String, Error> {
+    let c = Coordinate {
+        x: f64::from(lon),
+        y: f64::from(lat),
+    };
+    if !(-180..=180).contains(&lon) || !(-90..=90).contains(&lat) {
+        bail!(GeohashError::InvalidCoordinateRange { c });
+    }
+    encode(c, len)
+}
+
+/// Move a coordinate to the center of its length `len` cell.
+///
+/// Points snapped at the same length compare equal exactly when they share a
+/// cell, which makes it a quick way to deduplicate nearby points.
+///
+/// ### Examples
+///
+/// ```rust
+/// let c = geohash::snap(geohash::Coordinate { x: 10.0, y: 40.0 }, 1).expect("Invalid coordinate");
+///
+/// assert_eq!(c, geohash::Coordinate { x: 45.0, y: 22.5 });
+/// ```
+pub fn snap(c: Coordinate<f64>, len: usize) -> Result<Coordinate<f64>, Error> {
+    let (center, _, _) = decode(&encode(c, len)?)?;
+    Ok(center)
+}
+
+/// Move every coordinate to the center of its length `len` cell, see `snap`.
+/// Fails on the first coordinate out of range.
+pub fn snap_all(coords: &[Coordinate<f64>], len: usize) -> Result<Vec<Coordinate<f64>>, Error> {
+    coords.iter().map(|&c| snap(c, len)).collect()
+}
+
+/// Find the geohash of the point diametrically opposite the center of a
+/// cell, at the same length.
+///
+/// The latitude is negated and `180` added to the longitude, wrapped as by
+/// `normalize`. Half the world is a whole number of cells wide at any length,
+/// so the opposite point is itself a cell center and `antipode` undoes
+/// itself.
+///
+/// ### Examples
+///
+/// ```rust
+/// let hash = geohash::antipode("e71150").expect("Invalid hash string");
+///
+/// assert_eq!(geohash::antipode(&hash).unwrap(), "e71150");
+/// ```
+pub fn antipode(hash_str: &str) -> Result<String, Error> {
+    let (center, _, _) = decode(hash_str)?;
+    let opposite = normalize(Coordinate {
+        x: center.x + 180f64,
+        y: -center.y,
+    });
+    encode(opposite, hash_str.len())
+}
+
+/// Decode geohashes of mixed lengths onto the common grid of length
+/// `target_len` cells: each hash's center is moved to the center of the
+/// length `target_len` cell holding it, see `snap`.
+///
+/// A hash longer than `target_len` ends up on the center of its ancestor. A
+/// shorter one has its center on a corner shared by finer cells, and lands,
+/// as `encode` decides, on the center of the cell south-west of it. The first
+/// invalid hash fails the call with `GeohashError::InvalidAtIndex`, naming
+/// its index in `hashes`. A `target_len` of zero is rejected up front.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coords = geohash::decode_normalized(&["e71150dc9", "e71150"], 6).expect("Invalid hash");
+///
+/// assert_eq!(coords[0], coords[1]);
+/// ```
+pub fn decode_normalized(
+    hashes: &[&str],
+    target_len: usize,
+) -> Result<Vec<Coordinate<f64>>, Error> {
+    if target_len == 0 {
+        bail!(GeohashError::InvalidLength { len: target_len });
+    }
+    hashes
+        .iter()
+        .enumerate()
+        .map(|(index, hash)| {
+            let (c, _, _) =
+                decode(hash).map_err(|error| GeohashError::InvalidAtIndex { index, error })?;
+            snap(c, target_len)
+        })
+        .collect()
+}
+
+/// Find the length `len` cell a fraction `t` of the way from the center of
+/// cell `a` to the center of cell `b`, along the great circle joining them.
+///
+/// `t = 0` gives `a`'s center re-encoded at `len` and `t = 1` gives `b`'s;
+/// values outside `[0, 1]` carry on along the same great circle. Centers at
+/// opposite ends of the Earth have no single great circle between them, so
+/// they are interpolated linearly in longitude and latitude instead.
+///
+/// ### Examples
+///
+/// ```rust
+/// // "6" lies due north of "3"
+/// let start = geohash::interpolate_cells("3", "6", 0.25, 1).expect("Invalid hash string");
+/// let end = geohash::interpolate_cells("3", "6", 0.75, 1).expect("Invalid hash string");
+///
+/// assert_eq!((start.as_str(), end.as_str()), ("3", "6"));
+/// ```
+pub fn interpolate_cells(a: &str, b: &str, t: f64, len: usize) -> Result<String, Error> {
+    let (from, _, _) = decode(a)?;
+    let (to, _, _) = decode(b)?;
+    let p = to_unit_vector(from);
+    let q = to_unit_vector(to);
+    let cos_angle = (p[0] * q[0] + p[1] * q[1] + p[2] * q[2]).clamp(-1f64, 1f64);
+    let angle = cos_angle.acos();
+
+    let c = if angle < 1e-12 {
+        from
+    } else if (std::f64::consts::PI - angle) < 1e-12 {
+        Coordinate {
+            x: from.x + (to.x - from.x) * t,
+            y: from.y + (to.y - from.y) * t,
+        }
+    } else {
+        let wp = ((1f64 - t) * angle).sin() / angle.sin();
+        let wq = (t * angle).sin() / angle.sin();
+        let (x, y, z) = (
+            wp * p[0] + wq * q[0],
+            wp * p[1] + wq * q[1],
+            wp * p[2] + wq * q[2],
+        );
+        Coordinate {
+            x: y.atan2(x).to_degrees(),
+            y: z.atan2(x.hypot(y)).to_degrees(),
+        }
+    };
+    encode(normalize(c), len)
+}
+
+/// Point on the unit sphere for a coordinate, as `[x, y, z]`.
+fn to_unit_vector(c: Coordinate<f64>) -> [f64; 3] {
+    let (lon, lat) = (c.x.to_radians(), c.y.to_radians());
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+/// Pick a uniformly random coordinate inside a geohash cell.
+///
+/// Handy for generating fixtures and for blurring an exact location down to
+/// its cell. Requires the `rand` feature.
+///
+/// ### Examples
+///
+/// ```rust
+/// # #[cfg(feature = "rand")]
+/// # {
+/// let mut rng = rand::thread_rng();
+///
+/// let c = geohash::random_point_in("e71150", &mut rng).expect("Invalid hash string");
+///
+/// assert_eq!(geohash::encode(c, 6).unwrap(), "e71150");
+/// # }
+/// ```
+#[cfg(feature = "rand")]
+pub fn random_point_in<R: Rng + ?Sized>(
+    hash_str: &str,
+    rng: &mut R,
+) -> Result<Coordinate<f64>, Error> {
+    let rect = decode_bbox(hash_str)?;
+    Ok(Coordinate {
+        x: rng.gen_range(rect.min.x..=rect.max.x),
+        y: rng.gen_range(rect.min.y..=rect.max.y),
+    })
+}
@@ -1,12 +1,34 @@
+use std::collections::HashMap;
+
+use crate::bits::grid_from_hash;
 use crate::neighbors::Direction;
+use crate::precision::haversine;
+use crate::table::cached_neighbors;
 use crate::{Coordinate, GeohashError, Neighbors, Rect};
 
 use failure::Error;
+use num_traits::Float;
+
+#[cfg(feature = "arrayvec")]
+use arrayvec::ArrayString;
 
-static BASE32_CODES: &'static [char] = &[
+/// Maximum length of a geohash returned by `encode_small`.
+#[cfg(feature = "arrayvec")]
+pub const SMALL_HASH_CAPACITY: usize = 16;
+
+pub(crate) static BASE32_CODES: &[char] = &[
     '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a','b', 'c', 'd', 'e', 'f',
 ];
 
+/// Characters of the classic base32 geohash alphabet missing from ours.
+static BASE32_ONLY_CODES: &[char] = &[
+    'g', 'h', 'j', 'k', 'm', 'n', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
+];
+
+static UPPERCASE_CODES: &[char] = &[
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'A', 'B', 'C', 'D', 'E', 'F',
+];
+
 /// Encode a coordinate to a geohash with length `len`.
 ///
 /// ### Examples
@@ -30,25 +52,408 @@ static BASE32_CODES: &'static [char] = &[
 ///
 /// assert_eq!(geohash_string, "4d8c0f1817");
 /// ```
+///
+/// A length zero geohash names no cell, so `len` must be at least one:
+///
+/// ```rust
+/// let coord = geohash::Coordinate { x: -120.6623, y: 35.3003 };
+///
+/// assert!(geohash::encode(coord, 0).is_err());
+/// ```
 pub fn encode(c: Coordinate<f64>, len: usize) -> Result<String, Error> {
+    encode_with_codes(c, len, BASE32_CODES)
+}
+
+/// Encode a coordinate to a geohash with length `len`, skipping the checks
+/// `encode` makes.
+///
+/// For hot loops over coordinates already known to be valid. The range check
+/// is skipped, so a coordinate outside `±180`/`±90`, or a NaN, yields a
+/// meaningless hash instead of an error, and a `len` of zero yields the empty
+/// hash. For valid input the result is the same as `encode`'s.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coord = geohash::Coordinate { x: -120.6623, y: 35.3003 };
+///
+/// assert_eq!(geohash::encode_unchecked(coord, 5), "4d8c0");
+/// ```
+pub fn encode_unchecked(c: Coordinate<f64>, len: usize) -> String {
     let mut out = String::with_capacity(len);
+    bisect(c, len, BASE32_CODES, false, earth_bounds(), |code| {
+        out.push(code)
+    });
+    out
+}
 
-    let mut bits_total: i8 = 0;
-    let mut hash_value: usize = 0;
-    let mut max_lat = 90f64;
-    let mut min_lat = -90f64;
-    let mut max_lon = 180f64;
-    let mut min_lon = -180f64;
+/// Encode a coordinate to a geohash with length `len`, using the uppercase
+/// digits `A`–`F` instead of `a`–`f`. Decoding accepts either case.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coord = geohash::Coordinate { x: -120.6623, y: 35.3003 };
+///
+/// let geohash_string = geohash::encode_uppercase(coord, 10).expect("Invalid coordinate");
+///
+/// assert_eq!(geohash_string, "4D8C0F1817");
+/// ```
+pub fn encode_uppercase(c: Coordinate<f64>, len: usize) -> Result<String, Error> {
+    encode_with_codes(c, len, UPPERCASE_CODES)
+}
+
+/// Encode a coordinate to a geohash with length `len`, assigning points that
+/// lie exactly on a cell boundary to the cell above or to the right of it.
+///
+/// `encode` puts such points in the lower/left cell instead. Either way the
+/// outcome is deterministic: every bisection point is a multiple of a power of
+/// two, exactly representable as an `f64`, so the comparison against it does
+/// not depend on rounding.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coord = geohash::Coordinate { x: 0.0, y: 0.0 };
+///
+/// assert_eq!(geohash::encode(coord, 1).unwrap(), "3");
+/// assert_eq!(geohash::encode_stable(coord, 1).unwrap(), "c");
+/// ```
+pub fn encode_stable(c: Coordinate<f64>, len: usize) -> Result<String, Error> {
+    let mut out = String::with_capacity(len);
+    encode_into(c, len, BASE32_CODES, true, earth_bounds(), |code| {
+        out.push(code)
+    })?;
+    Ok(out)
+}
+
+/// Which axis the first bit of a geohash splits, see `encode_with_axis_order`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AxisOrder {
+    /// Longitude first, as `encode` and every other function of this crate.
+    LonFirst,
+    /// Latitude first, as some other geohash implementations do.
+    LatFirst,
+}
+
+/// Encode a coordinate to a geohash with length `len`, interleaving its bits
+/// in the given axis order.
+///
+/// Bits still alternate between the axes, only the one leading each
+/// character changes. The two orders produce incompatible hashes: a
+/// `LatFirst` hash means something else to `decode` and every other function
+/// here, so decode it with `decode_with_axis_order` only.
+///
+/// ### Examples
+///
+/// ```rust
+/// use geohash::AxisOrder;
+///
+/// let coord = geohash::Coordinate { x: 100.0, y: 10.0 };
+///
+/// assert_eq!(geohash::encode_with_axis_order(coord, 1, AxisOrder::LonFirst).unwrap(), "e");
+/// assert_eq!(geohash::encode_with_axis_order(coord, 1, AxisOrder::LatFirst).unwrap(), "d");
+/// ```
+pub fn encode_with_axis_order(
+    c: Coordinate<f64>,
+    len: usize,
+    order: AxisOrder,
+) -> Result<String, Error> {
+    match order {
+        AxisOrder::LonFirst => encode(c, len),
+        AxisOrder::LatFirst => {
+            if c.x < -180f64 || c.x > 180f64 || c.y < -90f64 || c.y > 90f64 {
+                bail!(GeohashError::InvalidCoordinateRange { c });
+            }
+            // Bisecting the swapped coordinate over the swapped domain splits
+            // latitude first
+            let swapped = Coordinate { x: c.y, y: c.x };
+            let bounds = Rect {
+                min: Coordinate {
+                    x: -90f64,
+                    y: -180f64,
+                },
+                max: Coordinate {
+                    x: 90f64,
+                    y: 180f64,
+                },
+            };
+            let mut out = String::with_capacity(len);
+            encode_into(swapped, len, BASE32_CODES, false, bounds, |code| {
+                out.push(code)
+            })?;
+            Ok(out)
+        }
+    }
+}
+
+/// Decode a geohash interleaved in the given axis order to its cell's center
+/// and `(longitude, latitude)` error margins, see `encode_with_axis_order`.
+///
+/// ### Examples
+///
+/// ```rust
+/// use geohash::AxisOrder;
+///
+/// let (c, _, _) = geohash::decode_with_axis_order("d", AxisOrder::LatFirst).unwrap();
+///
+/// assert_eq!((c.x, c.y), (135f64, 22.5f64));
+/// ```
+pub fn decode_with_axis_order(
+    hash_str: &str,
+    order: AxisOrder,
+) -> Result<(Coordinate<f64>, f64, f64), Error> {
+    match order {
+        AxisOrder::LonFirst => decode(hash_str),
+        AxisOrder::LatFirst => {
+            let swapped = decode_values(
+                hash_str.chars().map(hash_value_of_char),
+                -90f64,
+                90f64,
+                -180f64,
+                180f64,
+            )?;
+            Ok(center_and_error(Rect {
+                min: Coordinate {
+                    x: swapped.min.y,
+                    y: swapped.min.x,
+                },
+                max: Coordinate {
+                    x: swapped.max.y,
+                    y: swapped.max.x,
+                },
+            }))
+        }
+    }
+}
+
+/// How `encode_with_mode` settles a point lying exactly on a cell boundary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EncodeMode {
+    /// Always the cell west or south of the boundary, like `encode`.
+    Truncate,
+    /// The cell whose column or row index is even, like rounding half to
+    /// even, so boundary points are split evenly between the two sides.
+    Nearest,
+}
+
+/// Encode a coordinate to a geohash with length `len`, settling points on a
+/// cell boundary as `mode` says.
+///
+/// The cell holding a point is also the one whose center is nearest, so both
+/// modes agree everywhere except on a boundary, where the point is as close
+/// to two centers. `Truncate` then always takes the west or south cell, which
+/// biases gridded data such as whole-degree points to one side; `Nearest`
+/// breaks the tie toward the even column or row.
+///
+/// ### Examples
+///
+/// ```rust
+/// use geohash::EncodeMode;
+///
+/// // 0 is the edge between columns 1 and 2 and rows 1 and 2
+/// let coord = geohash::Coordinate { x: 0.0, y: 0.0 };
+///
+/// assert_eq!(geohash::encode_with_mode(coord, 1, EncodeMode::Truncate).unwrap(), "3");
+/// assert_eq!(geohash::encode_with_mode(coord, 1, EncodeMode::Nearest).unwrap(), "c");
+/// ```
+pub fn encode_with_mode(c: Coordinate<f64>, len: usize, mode: EncodeMode) -> Result<String, Error> {
+    let hash = encode(c, len)?;
+    if mode == EncodeMode::Truncate {
+        return Ok(hash);
+    }
+    // `encode` put the point in the lower cell; move it up where that cell
+    // has an odd index and the point sits on its upper edge
+    let last = hash_value_of_char(hash.chars().last().unwrap())?;
+    let rect = decode_bbox(&hash)?;
+    let east = c.x == rect.max.x && c.x < 180f64 && last & 0b10 != 0;
+    let north = c.y == rect.max.y && c.y < 90f64 && last & 0b01 != 0;
+    if !east && !north {
+        return Ok(hash);
+    }
+    let (center, lon_err, lat_err) = center_and_error(rect);
+    let moved = Coordinate {
+        x: if east {
+            center.x + 2f64 * lon_err
+        } else {
+            center.x
+        },
+        y: if north {
+            center.y + 2f64 * lat_err
+        } else {
+            center.y
+        },
+    };
+    encode(moved, len)
+}
+
+/// Encode a `f32` coordinate to a geohash with length `len`.
+///
+/// The cell is bisected in `f32` arithmetic. Every character consumes 2 bits
+/// per axis, and a cell edge of length `n` is `-180` plus a multiple of
+/// `45 * 2^(3 - 2n)` degrees of longitude, or `-90` plus a multiple of
+/// `45 * 2^(2 - 2n)` of latitude. Near the antimeridian and the poles that
+/// takes 6 bits for the factor 45 plus 2 per character, so the edges fit the
+/// 24-bit `f32` mantissa up to 9 characters: up to that length the hash is
+/// exactly `encode`'s for the same point. From 10 characters on, the
+/// midpoints round and points near an edge can land in the wrong cell; stick
+/// to `encode` when more precision is needed.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coord = geohash::Coordinate { x: -120.6623f32, y: 35.3003f32 };
+///
+/// let geohash_string = geohash::encode_f32(coord, 5).expect("Invalid coordinate");
+///
+/// assert_eq!(geohash_string, "4d8c0");
+/// ```
+pub fn encode_f32(c: Coordinate<f32>, len: usize) -> Result<String, Error> {
+    let mut out = String::with_capacity(len);
+    encode_into(c, len, BASE32_CODES, false, earth_bounds(), |code| {
+        out.push(code)
+    })?;
+    Ok(out)
+}
 
-    if c.x < min_lon || c.x > max_lon || c.y < min_lat || c.y > max_lat {
+/// Encode a coordinate to a geohash with length `len`, bisecting the given
+/// longitude/latitude domain instead of the Earth's `±180`/`±90`.
+///
+/// This covers bodies whose coordinates use other ranges, such as `0` to
+/// `360` degrees of longitude. Decode the result with
+/// `decode_bbox_with_bounds` and the same domain.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coord = geohash::Coordinate { x: 300.0, y: -60.0 };
+///
+/// let geohash_string = geohash::encode_with_bounds(coord, 1, 0.0, 360.0, -90.0, 90.0)
+///     .expect("Invalid coordinate");
+///
+/// assert_eq!(geohash_string, "a");
+/// ```
+pub fn encode_with_bounds(
+    c: Coordinate<f64>,
+    len: usize,
+    min_lon: f64,
+    max_lon: f64,
+    min_lat: f64,
+    max_lat: f64,
+) -> Result<String, Error> {
+    let bounds = Rect {
+        min: Coordinate {
+            x: min_lon,
+            y: min_lat,
+        },
+        max: Coordinate {
+            x: max_lon,
+            y: max_lat,
+        },
+    };
+    let mut out = String::with_capacity(len);
+    encode_into(c, len, BASE32_CODES, false, bounds, |code| out.push(code))?;
+    Ok(out)
+}
+
+fn earth_bounds<T: Float>() -> Rect<T> {
+    let max_lon = T::from(180).unwrap();
+    let max_lat = T::from(90).unwrap();
+    Rect {
+        min: Coordinate {
+            x: -max_lon,
+            y: -max_lat,
+        },
+        max: Coordinate {
+            x: max_lon,
+            y: max_lat,
+        },
+    }
+}
+
+fn encode_with_codes(c: Coordinate<f64>, len: usize, codes: &[char]) -> Result<String, Error> {
+    let mut out = String::with_capacity(len);
+    encode_into(c, len, codes, false, earth_bounds(), |code| out.push(code))?;
+    Ok(out)
+}
+
+/// Encode a coordinate to a geohash with length `len` without allocating.
+///
+/// Hex geohashes are at most 16 characters long at useful `f64` precision,
+/// so the result fits in a stack-allocated `ArrayString`. Lengths above
+/// `SMALL_HASH_CAPACITY` are rejected. Requires the `arrayvec` feature.
+///
+/// ### Examples
+///
+/// ```rust
+/// # #[cfg(feature = "arrayvec")]
+/// # {
+/// let coord = geohash::Coordinate { x: -120.6623, y: 35.3003 };
+///
+/// let geohash_string = geohash::encode_small(coord, 10).expect("Invalid coordinate");
+///
+/// assert_eq!(geohash_string.as_str(), "4d8c0f1817");
+/// # }
+/// ```
+#[cfg(feature = "arrayvec")]
+pub fn encode_small(
+    c: Coordinate<f64>,
+    len: usize,
+) -> Result<ArrayString<SMALL_HASH_CAPACITY>, Error> {
+    if len > SMALL_HASH_CAPACITY {
+        bail!(GeohashError::InvalidLength { len });
+    }
+    let mut out = ArrayString::new();
+    encode_into(c, len, BASE32_CODES, false, earth_bounds(), |code| {
+        out.push(code)
+    })?;
+    Ok(out)
+}
+
+fn encode_into<T: Float, F: FnMut(char)>(
+    c: Coordinate<T>,
+    len: usize,
+    codes: &[char],
+    upper_inclusive: bool,
+    bounds: Rect<T>,
+    push: F,
+) -> Result<(), Error> {
+    if len == 0 {
+        bail!(GeohashError::InvalidLength { len });
+    }
+    if c.x < bounds.min.x || c.x > bounds.max.x || c.y < bounds.min.y || c.y > bounds.max.y {
+        let c = Coordinate {
+            x: c.x.to_f64().unwrap(),
+            y: c.y.to_f64().unwrap(),
+        };
         bail!(GeohashError::InvalidCoordinateRange { c });
     }
+    bisect(c, len, codes, upper_inclusive, bounds, push);
+    Ok(())
+}
+
+/// The bisection behind `encode_into`, trusting `c` to lie within `bounds`.
+fn bisect<T: Float, F: FnMut(char)>(
+    c: Coordinate<T>,
+    len: usize,
+    codes: &[char],
+    upper_inclusive: bool,
+    bounds: Rect<T>,
+    mut push: F,
+) {
+    let two = T::one() + T::one();
+    let mut bits_total: i8 = 0;
+    let mut hash_value: usize = 0;
+    let mut max_lat = bounds.max.y;
+    let mut min_lat = bounds.min.y;
+    let mut max_lon = bounds.max.x;
+    let mut min_lon = bounds.min.x;
 
-    while out.len() < len {
+    for _ in 0..len {
         for _ in 0..4 {
             if bits_total % 2 == 0 {
-                let mid = (max_lon + min_lon) / 2f64;
-                if c.x > mid {
+                let mid = (max_lon + min_lon) / two;
+                if c.x > mid || (upper_inclusive && c.x == mid) {
                     hash_value = (hash_value << 1) + 1usize;
                     min_lon = mid;
                 } else {
@@ -56,8 +461,8 @@ pub fn encode(c: Coordinate<f64>, len: usize) -> Result<String, Error> {
                     max_lon = mid;
                 }
             } else {
-                let mid = (max_lat + min_lat) / 2f64;
-                if c.y > mid {
+                let mid = (max_lat + min_lat) / two;
+                if c.y > mid || (upper_inclusive && c.y == mid) {
                     hash_value = (hash_value << 1) + 1usize;
                     min_lat = mid;
                 } else {
@@ -68,11 +473,10 @@ pub fn encode(c: Coordinate<f64>, len: usize) -> Result<String, Error> {
             bits_total += 1;
         }
 
-        let code: char = BASE32_CODES[hash_value];
-        out.push(code);
+        let code: char = codes[hash_value];
+        push(code);
         hash_value = 0;
     }
-    Ok(out)
 }
 
 /// Decode geohash string into latitude, longitude
@@ -81,27 +485,129 @@ pub fn encode(c: Coordinate<f64>, len: usize) -> Result<String, Error> {
 /// Geohash encoded `&str`
 ///
 /// Returns:
-/// A four-element tuple describs a bound box:
-/// * min_lat
-/// * max_lat
-/// * min_lon
-/// * max_lon
+/// A `Rect` describing the bounding box of the cell, with `x` holding the
+/// longitude and `y` the latitude. See `decode_bounds` for the same box as a
+/// plain tuple.
+///
+/// `Rect` is re-exported from `geo_types`, so the box can be passed to `geo`
+/// and the rest of the `geo_types` ecosystem as is, with no conversion or
+/// extra feature.
+///
+/// ### Examples
+///
+/// ```rust
+/// let rect: geo_types::Rect<f64> = geohash::decode_bbox("c").expect("Invalid hash string");
+///
+/// assert_eq!((rect.min.x, rect.min.y), (0f64, 0f64));
+/// assert_eq!((rect.max.x, rect.max.y), (90f64, 45f64));
+/// ```
 pub fn decode_bbox(hash_str: &str) -> Result<Rect<f64>, Error> {
+    decode_bbox_with_bounds(hash_str, -180f64, 180f64, -90f64, 90f64)
+}
+
+/// Decode a geohash produced by `encode_with_bounds` into the bounding box of
+/// its cell, bisecting the same longitude/latitude domain.
+///
+/// ### Examples
+///
+/// ```rust
+/// let rect = geohash::decode_bbox_with_bounds("a", 0.0, 360.0, -90.0, 90.0)
+///     .expect("Invalid hash string");
+///
+/// assert_eq!((rect.min.x, rect.min.y), (270f64, -90f64));
+/// assert_eq!((rect.max.x, rect.max.y), (360f64, -45f64));
+/// ```
+pub fn decode_bbox_with_bounds(
+    hash_str: &str,
+    min_lon: f64,
+    max_lon: f64,
+    min_lat: f64,
+    max_lat: f64,
+) -> Result<Rect<f64>, Error> {
+    decode_values(
+        hash_str.chars().map(hash_value_of_char),
+        min_lon,
+        max_lon,
+        min_lat,
+        max_lat,
+    )
+}
+
+/// Decode a geohash given as raw ASCII bytes into the bounding box of its
+/// cell, without building a `&str` first.
+///
+/// Bytes outside the alphabet, including any non-ASCII byte, are rejected
+/// with `GeohashError::InvalidHashCharacter`.
+///
+/// ### Examples
+///
+/// ```rust
+/// let rect = geohash::decode_bbox_bytes(b"4d8c0").expect("Invalid hash");
+///
+/// assert_eq!(rect, geohash::decode_bbox("4d8c0").unwrap());
+/// ```
+pub fn decode_bbox_bytes(hash: &[u8]) -> Result<Rect<f64>, Error> {
+    decode_values(
+        hash.iter().map(|&b| hash_value_of_char(b as char)),
+        -180f64,
+        180f64,
+        -90f64,
+        90f64,
+    )
+}
+
+/// Decode a geohash into the bounding box of its cell, computing each edge
+/// straight from the cell's integer grid position.
+///
+/// `decode_bbox` halves the box once per bit, which can round differently on
+/// either side of an edge at high precision. Here an edge is always
+/// `origin + index * step` with `step` a power of two fraction of the world,
+/// so adjacent cells of the same length report bit-identical shared edges.
+/// Hashes longer than 16 characters are rejected.
+///
+/// ### Examples
+///
+/// ```rust
+/// let west = geohash::cell_bounds_exact("e71150dc9").expect("Invalid hash string");
+/// let east = geohash::cell_bounds_exact("e71150dcb").expect("Invalid hash string");
+///
+/// assert_eq!(west.max.x, east.min.x);
+/// ```
+pub fn cell_bounds_exact(hash_str: &str) -> Result<Rect<f64>, Error> {
+    let (col, row) = grid_from_hash(hash_str)?;
+    let cells_per_axis = (1u64 << (2 * hash_str.len())) as f64;
+    let lon_step = 360f64 / cells_per_axis;
+    let lat_step = 180f64 / cells_per_axis;
+    Ok(Rect {
+        min: Coordinate {
+            x: -180f64 + col as f64 * lon_step,
+            y: -90f64 + row as f64 * lat_step,
+        },
+        max: Coordinate {
+            x: -180f64 + (col + 1) as f64 * lon_step,
+            y: -90f64 + (row + 1) as f64 * lat_step,
+        },
+    })
+}
+
+fn decode_values<T: Float, I: Iterator<Item = Result<usize, Error>>>(
+    hash_values: I,
+    mut min_lon: T,
+    mut max_lon: T,
+    mut min_lat: T,
+    mut max_lat: T,
+) -> Result<Rect<T>, Error> {
+    let two = T::one() + T::one();
     let mut is_lon = true;
-    let mut max_lat = 90f64;
-    let mut min_lat = -90f64;
-    let mut max_lon = 180f64;
-    let mut min_lon = -180f64;
-    let mut mid: f64;
-    let mut hash_value: usize;
+    let mut mid: T;
 
-    for c in hash_str.chars() {
-        hash_value = hash_value_of_char(c)?;
+    for hash_value in hash_values {
+        let hash_value = hash_value?;
 
         for bs in 0..4 {
             let bit = (hash_value >> (3 - bs)) & 1usize;
             if is_lon {
-                mid = (max_lon + min_lon) / 2f64;
+                mid = (max_lon + min_lon) / two;
 
                 if bit == 1 {
                     min_lon = mid;
@@ -109,7 +615,7 @@ pub fn decode_bbox(hash_str: &str) -> Result<Rect<f64>, Error> {
                     max_lon = mid;
                 }
             } else {
-                mid = (max_lat + min_lat) / 2f64;
+                mid = (max_lat + min_lat) / two;
 
                 if bit == 1 {
                     min_lat = mid;
@@ -133,14 +639,174 @@ pub fn decode_bbox(hash_str: &str) -> Result<Rect<f64>, Error> {
     })
 }
 
-fn hash_value_of_char(c: char) -> Result<usize, Error> {
+/// Decode geohash string into its bounding box as a tuple.
+///
+/// Parameters:
+/// Geohash encoded `&str`
+///
+/// Returns:
+/// A four-element tuple describing a bound box:
+/// * min_lat
+/// * max_lat
+/// * min_lon
+/// * max_lon
+///
+/// ### Examples
+///
+/// ```rust
+/// let bounds = geohash::decode_bounds("c").expect("Invalid hash string");
+///
+/// assert_eq!(bounds, (0f64, 45f64, 0f64, 90f64));
+/// ```
+pub fn decode_bounds(hash_str: &str) -> Result<(f64, f64, f64, f64), Error> {
+    let rect = decode_bbox(hash_str)?;
+    Ok((rect.min.y, rect.max.y, rect.min.x, rect.max.x))
+}
+
+/// Measure how far a coordinate lies from the edges of the length `len` cell
+/// enclosing it. The return value is the `(north, east, south, west)`
+/// distances, in degrees.
+///
+/// A small distance means the point is close to leaving its cell, so a search
+/// around it should also scan the neighbor on that side.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coord = geohash::Coordinate { x: 10.0, y: 40.0 };
+///
+/// let distances = geohash::edge_distances(coord, 1).expect("Invalid coordinate");
+///
+/// assert_eq!(distances, (5f64, 80f64, 40f64, 10f64));
+/// ```
+pub fn edge_distances(c: Coordinate<f64>, len: usize) -> Result<(f64, f64, f64, f64), Error> {
+    let rect = decode_bbox(&encode(c, len)?)?;
+    Ok((
+        rect.max.y - c.y,
+        rect.max.x - c.x,
+        c.y - rect.min.y,
+        c.x - rect.min.x,
+    ))
+}
+
+/// Measure how far a coordinate lies from the corners of the length `len`
+/// cell enclosing it. The return value is the `[north-east, south-east,
+/// south-west, north-west]` distances, in meters.
+///
+/// Where `edge_distances` tells which orthogonal neighbors a radius search
+/// has to scan, these tell which diagonal ones: when the distance to a corner
+/// exceeds the radius, the diagonal neighbor beyond it can be skipped.
+/// Distances are great circle distances on a spherical Earth of mean radius.
+///
+/// ### Examples
+///
+/// ```rust
+/// let (center, _, _) = geohash::decode("c").expect("Invalid hash string");
+///
+/// let [ne, se, sw, nw] = geohash::corner_distances(center, 1).expect("Invalid coordinate");
+///
+/// // meridians converge, so the northern corners are closer
+/// assert_eq!((ne, se), (nw, sw));
+/// assert!(ne < se);
+/// ```
+pub fn corner_distances(c: Coordinate<f64>, len: usize) -> Result<[f64; 4], Error> {
+    let rect = decode_bbox(&encode(c, len)?)?;
+    let corner = |x, y| haversine(c, Coordinate { x, y });
+    Ok([
+        corner(rect.max.x, rect.max.y),
+        corner(rect.max.x, rect.min.y),
+        corner(rect.min.x, rect.min.y),
+        corner(rect.min.x, rect.max.y),
+    ])
+}
+
+/// Encode a coordinate to a geohash with length `len`, along with where the
+/// coordinate falls within the cell. The return value is the
+/// `(hash, x_offset, y_offset)` triple, the offsets being the fractions of the
+/// cell's width and height between its west and south edges and the point.
+///
+/// The offsets keep the precision the hash drops, so the point can later be
+/// rebuilt from the hash with `decode_with_offset`.
+///
+/// The offsets lie in the closed range `[0, 1]`, not `[0, 1)`: `encode`
+/// assigns a point on a boundary to the cell west or south of it, so a point
+/// on a cell's east or north edge, including the antimeridian at `180` and
+/// the north pole, has an offset of exactly `1`.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coord = geohash::Coordinate { x: 45.0, y: 22.5 };
+///
+/// let (hash, x, y) = geohash::encode_with_offset(coord, 1).expect("Invalid coordinate");
+///
+/// assert_eq!(hash, "c");
+/// assert_eq!((x, y), (0.5, 0.5));
+///
+/// // (0, 0) lies on the north east corner of "3ff"
+/// let origin = geohash::Coordinate { x: 0.0, y: 0.0 };
+///
+/// let (hash, x, y) = geohash::encode_with_offset(origin, 3).expect("Invalid coordinate");
+///
+/// assert_eq!(hash, "3ff");
+/// assert_eq!((x, y), (1.0, 1.0));
+/// ```
+pub fn encode_with_offset(c: Coordinate<f64>, len: usize) -> Result<(String, f64, f64), Error> {
+    let hash = encode(c, len)?;
+    let rect = decode_bbox(&hash)?;
+    let x = (c.x - rect.min.x) / (rect.max.x - rect.min.x);
+    let y = (c.y - rect.min.y) / (rect.max.y - rect.min.y);
+    Ok((hash, x, y))
+}
+
+pub(crate) fn hash_value_of_char(c: char) -> Result<usize, Error> {
+    Ok(char_value(c)?)
+}
+
+fn char_value(c: char) -> Result<usize, GeohashError> {
     let ord = c as usize;
-    if 48 <= ord && ord <= 57 {
-        return Ok(ord - 48);
-    } else if 97 <= ord && ord <= 102{
-        return Ok(ord - 87);
+    if (48..=57).contains(&ord) {
+        Ok(ord - 48)
+    } else if (97..=102).contains(&ord) {
+        Ok(ord - 87)
+    } else if (65..=70).contains(&ord) {
+        Ok(ord - 55)
+    } else if BASE32_ONLY_CODES.contains(&c.to_ascii_lowercase()) {
+        Err(GeohashError::Base32HashCharacter { character: c })
+    } else {
+        Err(GeohashError::InvalidHashCharacter { character: c })
     }
-    Err(GeohashError::InvalidHashCharacter { character: c })?
+}
+
+/// Check a column of geohashes, reporting for each one either `Ok` or the
+/// position of its first invalid character along with the error.
+///
+/// Results line up with `hashes`, so bad rows can be reported and skipped
+/// while the good ones are kept. Positions count characters, not bytes.
+///
+/// ### Examples
+///
+/// ```rust
+/// use geohash::GeohashError;
+///
+/// let results = geohash::validate_batch(&["e7115", "e71z5"]);
+///
+/// assert!(results[0].is_ok());
+/// match &results[1] {
+///     Err((3, GeohashError::Base32HashCharacter { character: 'z' })) => {}
+///     other => panic!("unexpected result: {:?}", other),
+/// }
+/// ```
+pub fn validate_batch(hashes: &[&str]) -> Vec<Result<(), (usize, GeohashError)>> {
+    hashes
+        .iter()
+        .map(|hash| {
+            for (position, c) in hash.chars().enumerate() {
+                char_value(c).map_err(|e| (position, e))?;
+            }
+            Ok(())
+        })
+        .collect()
 }
 
 /// Decode a geohash into a coordinate with some longitude/latitude error. The
@@ -188,33 +854,265 @@ fn hash_value_of_char(c: char) -> Result<usize, Error> {
 /// );
 /// ```
 pub fn decode(hash_str: &str) -> Result<(Coordinate<f64>, f64, f64), Error> {
+    Ok(center_and_error(decode_bbox(hash_str)?))
+}
+
+/// Decode a geohash to its cell's center and `(longitude, latitude)` error
+/// margins like `decode`, bisecting the cell in `f32` arithmetic.
+///
+/// Meant for rendering and other batch work that has no use for `f64`
+/// precision. Cell edges are exact in `f32` up to 9 characters, see
+/// `encode_f32`, so up to that length the result is `decode`'s rounded to
+/// `f32`. Past it the 24-bit `f32` mantissa runs out near the antimeridian
+/// and the poles, and the edges round.
+///
+/// ### Examples
+///
+/// ```rust
+/// let (c, dx, dy) = geohash::decode_f32("c").expect("Invalid hash string");
+///
+/// assert_eq!((c.x, c.y), (45f32, 22.5f32));
+/// assert_eq!((dx, dy), (45f32, 22.5f32));
+/// ```
+pub fn decode_f32(hash_str: &str) -> Result<(Coordinate<f32>, f32, f32), Error> {
+    let rect = decode_values(
+        hash_str.chars().map(hash_value_of_char),
+        -180f32,
+        180f32,
+        -90f32,
+        90f32,
+    )?;
+    Ok(center_and_error(rect))
+}
+
+/// Decode a geohash into the point at the given fractions of its cell's width
+/// and height, measured from the west and south edges, instead of its center.
+///
+/// This undoes `encode_with_offset`: feeding it the hash and offsets that
+/// function returned gives back the original coordinate, up to float
+/// rounding. Offsets outside `[0, 1]` are rejected with
+/// `GeohashError::InvalidOffset`.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coord = geohash::decode_with_offset("c", 0.5, 0.5).expect("Invalid hash string");
+///
+/// assert_eq!(coord, geohash::Coordinate { x: 45.0, y: 22.5 });
+/// ```
+pub fn decode_with_offset(hash_str: &str, ox: f64, oy: f64) -> Result<Coordinate<f64>, Error> {
+    for &offset in &[ox, oy] {
+        if !(0f64..=1f64).contains(&offset) {
+            bail!(GeohashError::InvalidOffset { offset });
+        }
+    }
     let rect = decode_bbox(hash_str)?;
+    Ok(Coordinate {
+        x: rect.min.x + ox * (rect.max.x - rect.min.x),
+        y: rect.min.y + oy * (rect.max.y - rect.min.y),
+    })
+}
+
+/// Center of a cell along with its longitude and latitude error, as returned
+/// by `decode`.
+pub(crate) fn center_and_error<T: Float>(rect: Rect<T>) -> (Coordinate<T>, T, T) {
+    let two = T::one() + T::one();
     let c0 = rect.min;
     let c1 = rect.max;
-    Ok((
-            Coordinate {
-                x: (c0.x + c1.x) / 2f64,
-                y: (c0.y + c1.y) / 2f64,
-            },
-            (c1.x - c0.x) / 2f64,
-            (c1.y - c0.y) / 2f64,
-            ))
+    (
+        Coordinate {
+            x: (c0.x + c1.x) / two,
+            y: (c0.y + c1.y) / two,
+        },
+        (c1.x - c0.x) / two,
+        (c1.y - c0.y) / two,
+    )
+}
+
+/// Decode a geohash like `decode`, refusing hashes longer than `max_len`
+/// characters up front.
+///
+/// Use it on untrusted input to bound the work done per hash; the error is
+/// `GeohashError::HashTooLong`.
+///
+/// ### Examples
+///
+/// ```rust
+/// assert!(geohash::decode_with_max_len("4d8c0", 8).is_ok());
+/// assert!(geohash::decode_with_max_len("4d8c0f1817", 8).is_err());
+/// ```
+pub fn decode_with_max_len(
+    hash_str: &str,
+    max_len: usize,
+) -> Result<(Coordinate<f64>, f64, f64), Error> {
+    if hash_str.len() > max_len {
+        bail!(GeohashError::HashTooLong {
+            len: hash_str.len(),
+            max: max_len,
+        });
+    }
+    decode(hash_str)
+}
+
+/// Decode a geohash into the center of its cell as a `(longitude, latitude)`
+/// tuple, the axis order used by GeoJSON.
+///
+/// ### Examples
+///
+/// ```rust
+/// let (lon, lat) = geohash::center("c").expect("Invalid hash string");
+///
+/// assert_eq!((lon, lat), (45f64, 22.5f64));
+/// ```
+pub fn center(hash_str: &str) -> Result<(f64, f64), Error> {
+    let (c, _, _) = decode(hash_str)?;
+    Ok((c.x, c.y))
+}
+
+/// Decode a geohash into the `(latitude, longitude)` of its cell's center,
+/// in the order most people write coordinates.
+///
+/// This is `center` with the axes swapped: `Coordinate` and the rest of the
+/// crate put the longitude first, as `x`.
+///
+/// ### Examples
+///
+/// ```rust
+/// let (lat, lon) = geohash::decode_lat_lon("c").expect("Invalid hash string");
+///
+/// assert_eq!((lat, lon), (22.5f64, 45f64));
+/// ```
+pub fn decode_lat_lon(hash_str: &str) -> Result<(f64, f64), Error> {
+    let (c, _, _) = decode(hash_str)?;
+    Ok((c.y, c.x))
+}
+
+/// Encode a latitude and longitude, in that order, to a geohash with length
+/// `len`. See `decode_lat_lon`.
+///
+/// ### Examples
+///
+/// ```rust
+/// let hash = geohash::encode_lat_lon(37.8324, 112.5584, 9).expect("Invalid coordinate");
+///
+/// assert_eq!(hash, "e71150dc9");
+/// ```
+pub fn encode_lat_lon(lat: f64, lon: f64, len: usize) -> Result<String, Error> {
+    encode(Coordinate { x: lon, y: lat }, len)
+}
+
+/// Lazily encode every coordinate of `iter` to a geohash with length `len`.
+///
+/// Coordinates are pulled from `iter` one at a time, so a stream never needs
+/// to be collected first. Each item is what `encode` returns for that
+/// coordinate, and an error does not end the iteration.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coords = vec![
+///     geohash::Coordinate { x: -120.6623, y: 35.3003 },
+///     geohash::Coordinate { x: 190.0, y: 0.0 },
+/// ];
+///
+/// let hashes: Vec<_> = geohash::encode_iter(coords, 5).collect();
+///
+/// assert_eq!(hashes[0].as_ref().unwrap(), "4d8c0");
+/// assert!(hashes[1].is_err());
+/// ```
+pub fn encode_iter<I: IntoIterator<Item = Coordinate<f64>>>(
+    iter: I,
+    len: usize,
+) -> impl Iterator<Item = Result<String, Error>> {
+    iter.into_iter().map(move |c| encode(c, len))
+}
+
+/// Check that encoding a geohash's center at the hash's own length gives the
+/// hash back.
+///
+/// A cell's center lies halfway between its edges, which are exact binary
+/// fractions of the domain, so it is exactly representable and strictly
+/// inside the cell; this holds for every valid hash. The function exists to
+/// verify that round trip, e.g. in tests sweeping many cells. Letters
+/// compare case insensitively. The empty hash is an error, as `encode`
+/// rejects a length of zero.
+///
+/// ### Examples
+///
+/// ```rust
+/// assert!(geohash::is_stable("e71150dc99").unwrap());
+/// assert!(geohash::is_stable("E71150DC99").unwrap());
+/// ```
+pub fn is_stable(hash_str: &str) -> Result<bool, Error> {
+    let (center, _, _) = decode(hash_str)?;
+    Ok(encode(center, hash_str.len())? == hash_str.to_lowercase())
 }
 
 /// Find neighboring geohashes for the given geohash and direction.
+///
+/// Cells in the top row have no northern neighbors and cells in the bottom
+/// row have no southern ones: asking for any of them returns
+/// `GeohashError::NeighborBeyondPole`.
+///
+/// ### Examples
+///
+/// ```rust
+/// assert_eq!(geohash::neighbor("2", geohash::Direction::N).unwrap(), "3");
+///
+/// assert!(geohash::neighbor("2", geohash::Direction::S).is_err());
+/// ```
 pub fn neighbor(hash_str: &str, direction: Direction) -> Result<String, Error> {
-    let (coord, lon_err, lat_err) = decode(hash_str)?;
-    let neighbor_coord = match direction.to_tuple() {
-        (dlat, dlng) => Coordinate {
-            x: coord.x + 2f64 * lon_err.abs() * dlng,
-            y: coord.y + 2f64 * lat_err.abs() * dlat,
-        },
+    let decoded = decode(hash_str)?;
+    neighbor_of(decoded, hash_str.len(), direction)
+}
+
+/// Decode a geohash once and return a function finding its neighbor in any
+/// direction on demand.
+///
+/// Useful when only some of the eight neighbors are needed, which `neighbors`
+/// would all compute. Like `neighbor`, the function fails for directions
+/// beyond the poles.
+///
+/// ### Examples
+///
+/// ```rust
+/// use geohash::Direction;
+///
+/// let neighbor = geohash::neighbor_fn("4d8c0f1817").expect("Invalid hash string");
+///
+/// assert_eq!(neighbor(Direction::N).unwrap(), "4d8c0f1842");
+/// assert_eq!(neighbor(Direction::E).unwrap(), "4d8c0f181d");
+/// ```
+pub fn neighbor_fn(hash_str: &str) -> Result<impl Fn(Direction) -> Result<String, Error>, Error> {
+    let decoded = decode(hash_str)?;
+    let len = hash_str.len();
+    Ok(move |direction| neighbor_of(decoded, len, direction))
+}
+
+/// Neighbor in `direction` of the length `len` cell `decode` returned
+/// `decoded` for.
+pub(crate) fn neighbor_of(
+    decoded: (Coordinate<f64>, f64, f64),
+    len: usize,
+    direction: Direction,
+) -> Result<String, Error> {
+    let (coord, lon_err, lat_err) = decoded;
+    let (dlat, dlng) = direction.to_tuple();
+    let neighbor_coord = Coordinate {
+        x: coord.x + 2f64 * lon_err.abs() * dlng,
+        y: coord.y + 2f64 * lat_err.abs() * dlat,
     };
-    encode(neighbor_coord, hash_str.len())
+    if neighbor_coord.y < -90f64 || neighbor_coord.y > 90f64 {
+        bail!(GeohashError::NeighborBeyondPole { direction });
+    }
+    encode(neighbor_coord, len)
 }
 
 /// Find all neighboring geohashes for the given geohash.
 ///
+/// Fails for cells in the top or bottom row, see `neighbor`. Hashes of up to
+/// `NEIGHBOR_TABLE_MAX_LEN` characters are looked up in a precomputed table.
+///
 /// ### Examples
 ///
 /// ```
@@ -237,14 +1135,73 @@ pub fn neighbor(hash_str: &str, direction: Direction) -> Result<String, Error> {
 /// );
 /// ```
 pub fn neighbors(hash_str: &str) -> Result<Neighbors, Error> {
+    match cached_neighbors(hash_str) {
+        Some(neighbors) => Ok(neighbors),
+        None => neighbors_uncached(hash_str),
+    }
+}
+
+pub(crate) fn neighbors_uncached(hash_str: &str) -> Result<Neighbors, Error> {
+    // decode once and only re-encode per direction
+    let decoded = decode(hash_str)?;
+    let len = hash_str.len();
     Ok(Neighbors {
-        sw: neighbor(hash_str, Direction::SW)?,
-        s: neighbor(hash_str, Direction::S)?,
-        se: neighbor(hash_str, Direction::SE)?,
-        w: neighbor(hash_str, Direction::W)?,
-        e: neighbor(hash_str, Direction::E)?,
-        nw: neighbor(hash_str, Direction::NW)?,
-        n: neighbor(hash_str, Direction::N)?,
-        ne: neighbor(hash_str, Direction::NE)?,
+        sw: neighbor_of(decoded, len, Direction::SW)?,
+        s: neighbor_of(decoded, len, Direction::S)?,
+        se: neighbor_of(decoded, len, Direction::SE)?,
+        w: neighbor_of(decoded, len, Direction::W)?,
+        e: neighbor_of(decoded, len, Direction::E)?,
+        nw: neighbor_of(decoded, len, Direction::NW)?,
+        n: neighbor_of(decoded, len, Direction::N)?,
+        ne: neighbor_of(decoded, len, Direction::NE)?,
     })
 }
+
+/// Return the given geohash together with all its neighbors, the 3x3 window
+/// commonly scanned around a point.
+///
+/// ### Examples
+///
+/// ```
+/// let (cell, neighbors) = geohash::with_neighbors("4d8c0f1817").expect("Invalid hash string");
+///
+/// assert_eq!(cell, "4d8c0f1817");
+/// assert_eq!(neighbors.n, "4d8c0f1842");
+/// ```
+pub fn with_neighbors(hash_str: &str) -> Result<(String, Neighbors), Error> {
+    let neighbors = neighbors(hash_str)?;
+    Ok((hash_str.to_owned(), neighbors))
+}
+
+/// Find all neighboring geohashes of every geohash in `hashes`, in the same
+/// order.
+///
+/// Repeated hashes are only worked out once. The first hash that fails, see
+/// `neighbors`, fails the call with `GeohashError::InvalidAtIndex`, naming
+/// its index in `hashes`.
+///
+/// ### Examples
+///
+/// ```rust
+/// let all = geohash::neighbors_all(&["4d8c0f1817", "e71150"]).expect("Invalid hash string");
+///
+/// assert_eq!(all[0], geohash::neighbors("4d8c0f1817").unwrap());
+/// assert_eq!(all[1], geohash::neighbors("e71150").unwrap());
+/// ```
+pub fn neighbors_all(hashes: &[&str]) -> Result<Vec<Neighbors>, Error> {
+    let mut seen: HashMap<&str, Neighbors> = HashMap::new();
+    let mut out = Vec::with_capacity(hashes.len());
+    for (index, &hash) in hashes.iter().enumerate() {
+        let found = match seen.get(hash) {
+            Some(found) => found.clone(),
+            None => {
+                let found = neighbors(hash)
+                    .map_err(|error| GeohashError::InvalidAtIndex { index, error })?;
+                seen.insert(hash, found.clone());
+                found
+            }
+        };
+        out.push(found);
+    }
+    Ok(out)
+}
@@ -0,0 +1,1027 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::iter;
+
+use crate::bits::{common_prefix_len, hash_from_grid, shared_prefix_len};
+use crate::core::{decode, decode_bbox, encode, hash_value_of_char, BASE32_CODES};
+use crate::precision::{cell_dimensions, haversine, EARTH_RADIUS};
+use crate::walker::GridWalker;
+use crate::{Coordinate, GeohashError, Rect, RectExt};
+
+use failure::Error;
+
+/// Longest geohash whose grid indices fit the arithmetic in `cover_bbox`.
+const MAX_GRID_LEN: usize = 16;
+
+/// Sample points per axis used by `cover_circle_weighted` to estimate how
+/// much of a cell lies inside the circle.
+const CIRCLE_SAMPLES: usize = 16;
+
+/// List the length `len` geohashes of every cell overlapping `rect`.
+///
+/// A point on the edge between two cells is assigned to a single cell the way
+/// `encode` assigns it, so every point of `rect` encodes, at length `len`, to
+/// one of the returned cells. Cells are listed row by row from the south-west
+/// corner. Lengths of 0 and above 16 are rejected.
+///
+/// ### Examples
+///
+/// ```rust
+/// let rect = geohash::Rect {
+///     min: geohash::Coordinate { x: -10.0, y: -10.0 },
+///     max: geohash::Coordinate { x: 10.0, y: 10.0 },
+/// };
+///
+/// let cells = geohash::cover_bbox(&rect, 1).expect("Invalid rect");
+///
+/// assert_eq!(cells, vec!["3", "9", "6", "c"]);
+/// ```
+pub fn cover_bbox(rect: &Rect<f64>, len: usize) -> Result<Vec<String>, Error> {
+    Ok(grid(len, rect)?.map(|(_, _, hash)| hash).collect())
+}
+
+/// List the same cells as `cover_bbox`, building the rows on rayon's thread
+/// pool. Requires the `rayon` feature.
+///
+/// Worth it for large regions at fine lengths, where building the hashes
+/// dominates. The cells come in the same order as from `cover_bbox`, row by
+/// row from the south-west corner, whatever the number of threads.
+///
+/// ### Examples
+///
+/// ```rust
+/// # #[cfg(feature = "rayon")]
+/// # {
+/// let rect = geohash::Rect {
+///     min: geohash::Coordinate { x: -10.0, y: -10.0 },
+///     max: geohash::Coordinate { x: 10.0, y: 10.0 },
+/// };
+///
+/// let cells = geohash::cover_bbox_parallel(&rect, 4).expect("Invalid rect");
+///
+/// assert_eq!(cells, geohash::cover_bbox(&rect, 4).unwrap());
+/// # }
+/// ```
+#[cfg(feature = "rayon")]
+pub fn cover_bbox_parallel(rect: &Rect<f64>, len: usize) -> Result<Vec<String>, Error> {
+    use rayon::prelude::*;
+
+    check_grid(rect, len)?;
+    let (min_col, max_col, min_row, max_row) = grid_span(rect, len);
+    #[cfg(feature = "log")]
+    trace_span(
+        "cover_bbox_parallel",
+        len,
+        (min_col, max_col, min_row, max_row),
+    );
+    Ok((min_row..max_row + 1)
+        .into_par_iter()
+        .flat_map_iter(|row| (min_col..max_col + 1).map(move |col| hash_from_grid(col, row, len)))
+        .collect())
+}
+
+/// List the length `len` geohashes tiling `rect` on the global grid.
+///
+/// Unlike `cover_bbox`, cells are treated as half-open, so a cell that only
+/// touches `rect` along an edge is left out: the bounding box of a geohash
+/// tiles to exactly that geohash, and adjacent rects share no tiles. A rect
+/// with no area still yields the cell holding its south-west corner. Cells
+/// are listed row by row from the south-west corner. Lengths of 0 and
+/// above 16 are rejected.
+///
+/// ### Examples
+///
+/// ```rust
+/// let rect = geohash::decode_bbox("9c").expect("Invalid hash string");
+///
+/// let tiles = geohash::tiles_aligned(&rect, 3).expect("Invalid rect");
+///
+/// assert_eq!(tiles.len(), 16);
+/// assert!(tiles.iter().all(|tile| tile.starts_with("9c")));
+/// ```
+pub fn tiles_aligned(rect: &Rect<f64>, len: usize) -> Result<Vec<String>, Error> {
+    check_grid(rect, len)?;
+
+    let cells_per_axis = 1u64 << (2 * len);
+    let tile_span = |min: f64, max: f64, origin: f64, extent: f64| {
+        let last = cells_per_axis - 1;
+        let first = (((min - origin) / extent * cells_per_axis as f64).floor() as u64).min(last);
+        let end = grid_index(max, origin, extent, cells_per_axis).max(first);
+        (first, end)
+    };
+    let (min_col, max_col) = tile_span(rect.min.x, rect.max.x, -180f64, 360f64);
+    let (min_row, max_row) = tile_span(rect.min.y, rect.max.y, -90f64, 180f64);
+
+    #[cfg(feature = "log")]
+    trace_span("tiles_aligned", len, (min_col, max_col, min_row, max_row));
+    let (cols, rows) = (max_col - min_col + 1, max_row - min_row + 1);
+    Ok(GridWalker::from_grid(min_col, min_row, len, cols, rows).collect())
+}
+
+/// Cover a circle of `radius_m` meters around `center` with length `len`
+/// cells, each paired with the fraction of its area lying inside the circle.
+///
+/// Cells fully inside the circle weigh `1.0`. The fraction of a cell is
+/// estimated from a regular grid of sample points across it, so cells the
+/// circle only clips between samples are left out. A circle too small to
+/// reach any sample still yields the cell holding its center, weighed by the
+/// ratio of the two areas. Distances are measured on a spherical Earth of
+/// mean radius. Like `RectExt::buffer`, the covered area is cut off at the
+/// antimeridian rather than wrapped. Cells come in the order of
+/// `cover_bbox`, and lengths of 0 and above 16 are rejected, as is a
+/// negative or non-finite radius.
+///
+/// ### Examples
+///
+/// ```rust
+/// let center = geohash::Coordinate { x: 112.5584, y: 37.8324 };
+///
+/// let cells = geohash::cover_circle_weighted(center, 1000.0, 8).expect("Invalid circle");
+///
+/// assert!(cells.iter().any(|&(_, weight)| weight == 1.0));
+/// assert!(cells.iter().all(|&(_, weight)| weight > 0.0 && weight <= 1.0));
+/// ```
+pub fn cover_circle_weighted(
+    center: Coordinate<f64>,
+    radius_m: f64,
+    len: usize,
+) -> Result<Vec<(String, f64)>, Error> {
+    if center.x < -180f64 || center.x > 180f64 || center.y < -90f64 || center.y > 90f64 {
+        bail!(GeohashError::InvalidCoordinateRange { c: center });
+    }
+    check_radius(radius_m)?;
+    let (dx, dy) = radius_degrees(center.y, radius_m);
+    let rect = Rect {
+        min: center,
+        max: center,
+    }
+    .buffer(dx, dy);
+
+    let center_hash = encode(center, len)?;
+    let mut out = vec![];
+    for (_, _, hash) in grid(len, &rect)? {
+        let weight = circle_fraction(&hash, center, radius_m, hash == center_hash)?;
+        if weight > 0f64 {
+            out.push((hash, weight));
+        }
+    }
+    Ok(out)
+}
+
+/// Estimate the fraction of a cell's area lying within `radius_m` meters of
+/// `center`, from 0 to 1.
+///
+/// The cell is sampled on a regular 16 by 16 grid, as in
+/// `cover_circle_weighted`, and the result is the share of samples inside
+/// the circle, a multiple of 1/256. The error comes from samples near the
+/// circle's edge, so it shrinks as the circle grows relative to the cell;
+/// it stays within a few percent once the circle spans several samples, but
+/// a circle passing between samples is missed entirely. A circle too small
+/// to reach any sample of the cell holding its center gives the ratio of the
+/// two areas instead. Distances are measured on a spherical Earth of mean
+/// radius. A negative or non-finite radius is rejected.
+///
+/// ### Examples
+///
+/// ```rust
+/// let (center, _, _) = geohash::decode("e71150").expect("Invalid hash string");
+///
+/// assert_eq!(geohash::cell_circle_overlap("e71150", center, 100_000.0).unwrap(), 1.0);
+/// assert_eq!(geohash::cell_circle_overlap("e72", center, 1000.0).unwrap(), 0.0);
+///
+/// let part = geohash::cell_circle_overlap("e71150", center, 1000.0).unwrap();
+/// assert!(part > 0.0 && part < 1.0);
+/// ```
+pub fn cell_circle_overlap(
+    hash_str: &str,
+    center: Coordinate<f64>,
+    radius_m: f64,
+) -> Result<f64, Error> {
+    if center.x < -180f64 || center.x > 180f64 || center.y < -90f64 || center.y > 90f64 {
+        bail!(GeohashError::InvalidCoordinateRange { c: center });
+    }
+    check_radius(radius_m)?;
+    decode_bbox(hash_str)?;
+    let holds_center =
+        hash_str.is_empty() || encode(center, hash_str.len())? == hash_str.to_lowercase();
+    circle_fraction(hash_str, center, radius_m, holds_center)
+}
+
+/// List the length `len` geohashes of every cell within `radius_m` meters
+/// of a path, for corridors along a route.
+///
+/// The path joins `points` in order with straight lines in
+/// longitude/latitude, like the edges of `cover_polygon_outline`, and every
+/// cell it passes through is included whatever the radius. So is every cell
+/// with a point within `radius_m` of a segment, found by sampling each cell
+/// on a regular grid as `cover_circle_weighted` does; cells the buffer only
+/// clips between samples are left out. Around a vertex the buffer is a
+/// circle, and a single point gives the cover of a circle. The nearest point
+/// of a segment is found in an equirectangular projection around the sample,
+/// which is accurate for segments much shorter than the Earth's radius, and
+/// distances are measured on a spherical Earth of mean radius. Nothing is
+/// wrapped across the antimeridian. The result is sorted and free of
+/// duplicates. An empty path and lengths of 0 or above 16 are errors.
+///
+/// ### Examples
+///
+/// ```rust
+/// let route = [
+///     geohash::Coordinate { x: 112.50, y: 37.80 },
+///     geohash::Coordinate { x: 112.56, y: 37.83 },
+///     geohash::Coordinate { x: 112.60, y: 37.90 },
+/// ];
+///
+/// let corridor = geohash::cover_buffered_path(&route, 500.0, 6).expect("Invalid path");
+/// let line = geohash::cover_buffered_path(&route, 0.0, 6).expect("Invalid path");
+///
+/// assert!(line.iter().all(|hash| corridor.contains(hash)));
+/// assert!(corridor.len() > line.len());
+/// ```
+pub fn cover_buffered_path(
+    points: &[Coordinate<f64>],
+    radius_m: f64,
+    len: usize,
+) -> Result<Vec<String>, Error> {
+    if points.is_empty() {
+        bail!(GeohashError::EmptyInput);
+    }
+    if len == 0 || len > MAX_GRID_LEN {
+        bail!(GeohashError::InvalidLength { len });
+    }
+    for &c in points {
+        if c.x < -180f64 || c.x > 180f64 || c.y < -90f64 || c.y > 90f64 {
+            bail!(GeohashError::InvalidCoordinateRange { c });
+        }
+    }
+
+    let mut out = vec![];
+    // A lone point is a segment of zero length
+    let segments: Vec<_> = if points.len() == 1 {
+        vec![(points[0], points[0])]
+    } else {
+        points.windows(2).map(|pair| (pair[0], pair[1])).collect()
+    };
+    for (a, b) in segments {
+        cover_segment(a, b, len, &mut out);
+        let (dx_a, dy) = radius_degrees(a.y, radius_m);
+        let (dx_b, _) = radius_degrees(b.y, radius_m);
+        let rect = Rect {
+            min: Coordinate {
+                x: a.x.min(b.x),
+                y: a.y.min(b.y),
+            },
+            max: Coordinate {
+                x: a.x.max(b.x),
+                y: a.y.max(b.y),
+            },
+        };
+        for (_, _, hash) in grid(len, &rect.buffer(dx_a.max(dx_b), dy))? {
+            let cell = decode_bbox(&hash)?;
+            let (width, height) = (cell.max.x - cell.min.x, cell.max.y - cell.min.y);
+            let near = (0..CIRCLE_SAMPLES * CIRCLE_SAMPLES).any(|k| {
+                let sample = Coordinate {
+                    x: cell.min.x
+                        + width * ((k / CIRCLE_SAMPLES) as f64 + 0.5) / CIRCLE_SAMPLES as f64,
+                    y: cell.min.y
+                        + height * ((k % CIRCLE_SAMPLES) as f64 + 0.5) / CIRCLE_SAMPLES as f64,
+                };
+                haversine(sample, nearest_on_segment(a, b, sample)) <= radius_m
+            });
+            if near {
+                out.push(hash);
+            }
+        }
+    }
+    out.sort();
+    out.dedup();
+    Ok(out)
+}
+
+/// List the length `len` geohashes of the cells the outline of a polygon
+/// passes through.
+///
+/// The polygon is closed, its last vertex joining back to the first, and each
+/// edge is a straight line in longitude/latitude. As with `cover_bbox`, every
+/// point of the outline encodes, at length `len`, to one of the returned
+/// cells. Edges are not wrapped across the antimeridian. The result is sorted
+/// and free of duplicates. An empty polygon and lengths of 0 or above 16 are
+/// errors.
+///
+/// ### Examples
+///
+/// ```rust
+/// let triangle = [
+///     geohash::Coordinate { x: -10.0, y: -10.0 },
+///     geohash::Coordinate { x: 10.0, y: -10.0 },
+///     geohash::Coordinate { x: 10.0, y: 10.0 },
+/// ];
+///
+/// let cells = geohash::cover_polygon_outline(&triangle, 1).expect("Invalid polygon");
+///
+/// // the hypotenuse passes through the corner shared with "6"
+/// assert_eq!(cells, vec!["3", "9", "c"]);
+/// ```
+pub fn cover_polygon_outline(
+    vertices: &[Coordinate<f64>],
+    len: usize,
+) -> Result<Vec<String>, Error> {
+    if vertices.is_empty() {
+        bail!(GeohashError::EmptyInput);
+    }
+    if len == 0 || len > MAX_GRID_LEN {
+        bail!(GeohashError::InvalidLength { len });
+    }
+    for &c in vertices {
+        if c.x < -180f64 || c.x > 180f64 || c.y < -90f64 || c.y > 90f64 {
+            bail!(GeohashError::InvalidCoordinateRange { c });
+        }
+    }
+
+    let mut out = vec![];
+    for (i, &a) in vertices.iter().enumerate() {
+        let b = vertices[(i + 1) % vertices.len()];
+        cover_segment(a, b, len, &mut out);
+    }
+    out.sort();
+    out.dedup();
+    Ok(out)
+}
+
+/// List the length `len` geohashes of every cell overlapping a simple
+/// polygon, concave or not.
+///
+/// A cell is included when any part of it overlaps the polygon: it either
+/// crosses the outline, see `cover_polygon_outline`, or lies entirely inside,
+/// which is tested on its center with the even-odd rule. Edges are straight
+/// lines in longitude/latitude and are not wrapped across the antimeridian.
+/// The result is sorted and free of duplicates. An empty polygon and lengths
+/// of 0 or above 16 are errors.
+///
+/// ### Examples
+///
+/// ```rust
+/// let square = [
+///     geohash::Coordinate { x: -100.0, y: -50.0 },
+///     geohash::Coordinate { x: 100.0, y: -50.0 },
+///     geohash::Coordinate { x: 100.0, y: 50.0 },
+///     geohash::Coordinate { x: -100.0, y: 50.0 },
+/// ];
+///
+/// let cells = geohash::cover_polygon(&square, 1).expect("Invalid polygon");
+///
+/// assert_eq!(cells.len(), 16);
+/// ```
+pub fn cover_polygon(vertices: &[Coordinate<f64>], len: usize) -> Result<Vec<String>, Error> {
+    let mut out = cover_polygon_outline(vertices, len)?;
+    let mut bbox = Rect {
+        min: vertices[0],
+        max: vertices[0],
+    };
+    for c in vertices {
+        bbox.min.x = bbox.min.x.min(c.x);
+        bbox.min.y = bbox.min.y.min(c.y);
+        bbox.max.x = bbox.max.x.max(c.x);
+        bbox.max.y = bbox.max.y.max(c.y);
+    }
+    for (_, _, hash) in grid(len, &bbox)? {
+        let (center, _, _) = decode(&hash)?;
+        if contains_point(vertices, center) {
+            out.push(hash);
+        }
+    }
+    out.sort();
+    out.dedup();
+    Ok(out)
+}
+
+/// Find the smallest and largest length `len` geohashes of the cells
+/// overlapping `rect`, for turning a bounding box query into a single
+/// `BETWEEN lo AND hi` range scan over sorted geohashes.
+///
+/// Geohashes sort in Z-order, which visits cells outside `rect` between
+/// those inside it, so the range is only a superset of `cover_bbox` and its
+/// results still need filtering. It can be far larger than the box: a box
+/// straddling the equator or the prime meridian spans most of the world's
+/// hashes. Lengths of 0 and above 16 are rejected.
+///
+/// ### Examples
+///
+/// ```rust
+/// let rect = geohash::Rect {
+///     min: geohash::Coordinate { x: -10.0, y: -10.0 },
+///     max: geohash::Coordinate { x: 10.0, y: 10.0 },
+/// };
+///
+/// let (lo, hi) = geohash::hash_range(&rect, 1).expect("Invalid rect");
+///
+/// assert_eq!((lo.as_str(), hi.as_str()), ("3", "c"));
+/// ```
+pub fn hash_range(rect: &Rect<f64>, len: usize) -> Result<(String, String), Error> {
+    check_grid(rect, len)?;
+    // Hash values grow with both the column and the row
+    let (min_col, max_col, min_row, max_row) = grid_span(rect, len);
+    Ok((
+        hash_from_grid(min_col, min_row, len),
+        hash_from_grid(max_col.max(min_col), max_row.max(min_row), len),
+    ))
+}
+
+/// Split the cells of `cover_bbox` into the fewest inclusive ranges of
+/// consecutive length `len` geohashes, listed in sort order.
+///
+/// Unlike the single range of `hash_range`, the ranges hold exactly the
+/// cells overlapping `rect`, so a range scan per entry fetches nothing
+/// outside the box. Z-order keeps the cells of a quadrant together, so a box
+/// usually needs about one range per row of cells or fewer. The ranges come
+/// from walking the quadtree down to the box edge only, so the cost grows
+/// with the perimeter of the box rather than its cell count. Lengths above
+/// 16 are rejected.
+///
+/// ### Examples
+///
+/// ```rust
+/// let rect = geohash::Rect {
+///     min: geohash::Coordinate { x: -10.0, y: -10.0 },
+///     max: geohash::Coordinate { x: 10.0, y: 10.0 },
+/// };
+///
+/// let ranges = geohash::hash_ranges(&rect, 1).expect("Invalid rect");
+///
+/// assert_eq!(
+///     ranges,
+///     vec![
+///         ("3".to_string(), "3".to_string()),
+///         ("6".to_string(), "6".to_string()),
+///         ("9".to_string(), "9".to_string()),
+///         ("c".to_string(), "c".to_string()),
+///     ]
+/// );
+/// ```
+pub fn hash_ranges(rect: &Rect<f64>, len: usize) -> Result<Vec<(String, String)>, Error> {
+    check_grid(rect, len)?;
+
+    let span = grid_span(rect, len);
+    let mut values: Vec<(u64, u64)> = vec![];
+    if span.0 <= span.1 && span.2 <= span.3 {
+        push_ranges(span, (0, 0), 0, 2 * len, &mut values);
+    }
+    let hash = |value: u64| {
+        let hash = format!("{:016x}", value);
+        hash[16 - len..].to_string()
+    };
+    Ok(values
+        .into_iter()
+        .map(|(first, last)| (hash(first), hash(last)))
+        .collect())
+}
+
+/// Push the value ranges of the cells of the quadtree node at `(col, row)`,
+/// `levels` splits above single cells, that fall inside `span`, merging each
+/// range into the previous one when they touch.
+fn push_ranges(
+    span: (u64, u64, u64, u64),
+    (col, row): (u64, u64),
+    value: u64,
+    levels: usize,
+    out: &mut Vec<(u64, u64)>,
+) {
+    let (min_col, max_col, min_row, max_row) = span;
+    let size = 1u64 << levels;
+    let (last_col, last_row) = (col + size - 1, row + size - 1);
+    if last_col < min_col || col > max_col || last_row < min_row || row > max_row {
+        return;
+    }
+    if col >= min_col && last_col <= max_col && row >= min_row && last_row <= max_row {
+        // A node's cells are the 4^levels values sharing its prefix
+        let first = (u128::from(value) << (2 * levels)) as u64;
+        let last = (((u128::from(value) + 1) << (2 * levels)) - 1) as u64;
+        match out.last_mut() {
+            Some(run) if run.1 + 1 == first => run.1 = last,
+            _ => out.push((first, last)),
+        }
+        return;
+    }
+
+    // Only nodes crossing the box edge get here, and never single cells
+    let half = size / 2;
+    for quadrant in 0..4u64 {
+        push_ranges(
+            span,
+            (col + (quadrant >> 1) * half, row + (quadrant & 1) * half),
+            value * 4 + quadrant,
+            levels - 1,
+            out,
+        );
+    }
+}
+
+/// Enumerate the cells `cover_bbox` returns for `rect` and `len`, along with
+/// their `(row, col)` position in the grid they form.
+///
+/// Row `0` is the southernmost row and column `0` the westernmost column, so
+/// the positions index straight into a 2D array the size of the cover.
+///
+/// ### Examples
+///
+/// ```rust
+/// let rect = geohash::Rect {
+///     min: geohash::Coordinate { x: -10.0, y: -10.0 },
+///     max: geohash::Coordinate { x: 10.0, y: 10.0 },
+/// };
+///
+/// let cells: Vec<_> = geohash::grid(1, &rect).expect("Invalid rect").collect();
+///
+/// assert_eq!(
+///     cells,
+///     vec![
+///         (0, 0, "3".to_string()),
+///         (0, 1, "9".to_string()),
+///         (1, 0, "6".to_string()),
+///         (1, 1, "c".to_string()),
+///     ]
+/// );
+/// ```
+pub fn grid(
+    len: usize,
+    rect: &Rect<f64>,
+) -> Result<impl Iterator<Item = (u32, u32, String)>, Error> {
+    check_grid(rect, len)?;
+
+    let (min_col, max_col, min_row, max_row) = grid_span(rect, len);
+    #[cfg(feature = "log")]
+    trace_span("grid", len, (min_col, max_col, min_row, max_row));
+    let cols = (max_col + 1).saturating_sub(min_col);
+    let rows = (max_row + 1).saturating_sub(min_row);
+    let mut walker = GridWalker::from_grid(min_col, min_row, len, cols, rows);
+    Ok(iter::from_fn(move || walker.step()).map(|(row, col, hash)| (row as u32, col as u32, hash)))
+}
+
+/// Count the cells `cover_bbox` would return for `rect` and `len`, without
+/// building them.
+///
+/// Use it to pick a coarser length, or give up, before enumerating a region
+/// that would produce millions of cells. Coordinates outside the valid range
+/// are clamped to it, and lengths `cover_bbox` rejects count as `usize::MAX`.
+///
+/// ### Examples
+///
+/// ```rust
+/// let rect = geohash::Rect {
+///     min: geohash::Coordinate { x: -10.0, y: -10.0 },
+///     max: geohash::Coordinate { x: 10.0, y: 10.0 },
+/// };
+///
+/// assert_eq!(geohash::count_cells(&rect, 1), 4);
+/// assert_eq!(geohash::count_cells(&rect, 4), 16 * 30);
+/// ```
+pub fn count_cells(rect: &Rect<f64>, len: usize) -> usize {
+    if len == 0 || len > MAX_GRID_LEN {
+        return usize::MAX;
+    }
+    let (min_col, max_col, min_row, max_row) = grid_span(rect, len);
+    if min_col > max_col || min_row > max_row {
+        return 0;
+    }
+    let cols = (max_col - min_col + 1) as usize;
+    let rows = (max_row - min_row + 1) as usize;
+    cols.saturating_mul(rows)
+}
+
+/// Reject radii that are negative, infinite or NaN.
+fn check_radius(radius_m: f64) -> Result<(), Error> {
+    if !radius_m.is_finite() || radius_m < 0f64 {
+        bail!(GeohashError::InvalidRadius { radius: radius_m });
+    }
+    Ok(())
+}
+
+/// Reject empty lengths, lengths too long for the grid arithmetic and rects
+/// reaching outside the valid coordinate range.
+fn check_grid(rect: &Rect<f64>, len: usize) -> Result<(), Error> {
+    if len == 0 || len > MAX_GRID_LEN {
+        bail!(GeohashError::InvalidLength { len });
+    }
+    for &c in &[rect.min, rect.max] {
+        if c.x < -180f64 || c.x > 180f64 || c.y < -90f64 || c.y > 90f64 {
+            bail!(GeohashError::InvalidCoordinateRange { c });
+        }
+    }
+    Ok(())
+}
+
+/// First and last column and row of the length `len` cells overlapping
+/// `rect`, as `(min_col, max_col, min_row, max_row)`.
+fn grid_span(rect: &Rect<f64>, len: usize) -> (u64, u64, u64, u64) {
+    let cells_per_axis = 1u64 << (2 * len);
+    (
+        grid_index(rect.min.x, -180f64, 360f64, cells_per_axis),
+        grid_index(rect.max.x, -180f64, 360f64, cells_per_axis),
+        grid_index(rect.min.y, -90f64, 180f64, cells_per_axis),
+        grid_index(rect.max.y, -90f64, 180f64, cells_per_axis),
+    )
+}
+
+/// Push the length `len` cells the straight segment from `a` to `b` passes
+/// through, including the cells `encode` puts its endpoints in.
+fn cover_segment(a: Coordinate<f64>, b: Coordinate<f64>, len: usize, out: &mut Vec<String>) {
+    let cells_per_axis = 1u64 << (2 * len);
+    let cells = cells_per_axis as f64;
+
+    // Split the segment wherever it crosses a column or row edge, so each
+    // piece lies in a single cell, identified by its midpoint.
+    let mut ts = vec![0f64, 1f64];
+    for &(from, to, origin, extent) in &[(a.x, b.x, -180f64, 360f64), (a.y, b.y, -90f64, 180f64)] {
+        let (u0, u1) = (
+            (from - origin) / extent * cells,
+            (to - origin) / extent * cells,
+        );
+        let mut edge = u0.min(u1).floor() + 1f64;
+        while edge < u0.max(u1) {
+            ts.push((edge - u0) / (u1 - u0));
+            edge += 1f64;
+        }
+    }
+    ts.sort_by(|l, r| l.partial_cmp(r).unwrap());
+
+    let mut points = vec![a, b];
+    for pair in ts.windows(2) {
+        let t = (pair[0] + pair[1]) / 2f64;
+        points.push(Coordinate {
+            x: a.x + (b.x - a.x) * t,
+            y: a.y + (b.y - a.y) * t,
+        });
+    }
+    for c in points {
+        let col = grid_index(c.x, -180f64, 360f64, cells_per_axis);
+        let row = grid_index(c.y, -90f64, 180f64, cells_per_axis);
+        out.push(hash_from_grid(col, row, len));
+    }
+}
+
+/// Share of the samples of a cell within `radius_m` of `center`, or, when
+/// none is and the cell holds the center, the ratio of the circle's area to
+/// the cell's.
+fn circle_fraction(
+    hash_str: &str,
+    center: Coordinate<f64>,
+    radius_m: f64,
+    holds_center: bool,
+) -> Result<f64, Error> {
+    let cell = decode_bbox(hash_str)?;
+    let (width, height) = (cell.max.x - cell.min.x, cell.max.y - cell.min.y);
+    let mut inside = 0;
+    for i in 0..CIRCLE_SAMPLES {
+        for j in 0..CIRCLE_SAMPLES {
+            let sample = Coordinate {
+                x: cell.min.x + width * (i as f64 + 0.5) / CIRCLE_SAMPLES as f64,
+                y: cell.min.y + height * (j as f64 + 0.5) / CIRCLE_SAMPLES as f64,
+            };
+            if haversine(center, sample) <= radius_m {
+                inside += 1;
+            }
+        }
+    }
+    if inside > 0 {
+        Ok(inside as f64 / (CIRCLE_SAMPLES * CIRCLE_SAMPLES) as f64)
+    } else if holds_center {
+        let (width, height) = cell_dimensions(hash_str)?;
+        let area = PI * radius_m * radius_m;
+        Ok((area / (width * height)).min(1f64))
+    } else {
+        Ok(0f64)
+    }
+}
+
+/// Half the width and height, in degrees, of the smallest rect holding a
+/// circle of `radius_m` meters centered at latitude `lat`. The width is the
+/// whole world once the circle reaches a pole.
+fn radius_degrees(lat: f64, radius_m: f64) -> (f64, f64) {
+    let angle = radius_m / EARTH_RADIUS;
+    let reach = angle.sin() / lat.to_radians().cos();
+    let dx = if angle >= PI / 2f64 || reach >= 1f64 {
+        360f64
+    } else {
+        reach.asin().to_degrees()
+    };
+    (dx, angle.to_degrees())
+}
+
+/// Point of the straight segment from `a` to `b` nearest to `c`, measured in
+/// an equirectangular projection centered on `c`.
+fn nearest_on_segment(
+    a: Coordinate<f64>,
+    b: Coordinate<f64>,
+    c: Coordinate<f64>,
+) -> Coordinate<f64> {
+    let scale = c.y.to_radians().cos();
+    let (abx, aby) = ((b.x - a.x) * scale, b.y - a.y);
+    let (acx, acy) = ((c.x - a.x) * scale, c.y - a.y);
+    let length = abx * abx + aby * aby;
+    let t = if length > 0f64 {
+        ((acx * abx + acy * aby) / length).clamp(0f64, 1f64)
+    } else {
+        0f64
+    };
+    Coordinate {
+        x: a.x + (b.x - a.x) * t,
+        y: a.y + (b.y - a.y) * t,
+    }
+}
+
+/// Whether `c` lies inside the polygon, by the even-odd rule: a ray cast
+/// east from `c` crosses its edges an odd number of times.
+fn contains_point(vertices: &[Coordinate<f64>], c: Coordinate<f64>) -> bool {
+    let mut inside = false;
+    for (i, &a) in vertices.iter().enumerate() {
+        let b = vertices[(i + 1) % vertices.len()];
+        if (a.y > c.y) != (b.y > c.y) && c.x < a.x + (c.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Log the size, corners and cell count of the span a coverage function is
+/// about to walk.
+#[cfg(feature = "log")]
+fn trace_span(function: &str, len: usize, span: (u64, u64, u64, u64)) {
+    let (min_col, max_col, min_row, max_row) = span;
+    let cols = (max_col + 1).saturating_sub(min_col);
+    let rows = (max_row + 1).saturating_sub(min_row);
+    trace!(
+        "{}: {} x {} grid of length {} cells from {} to {}, {} cells",
+        function,
+        cols,
+        rows,
+        len,
+        hash_from_grid(min_col, min_row, len),
+        hash_from_grid(max_col, max_row, len),
+        cols.saturating_mul(rows)
+    );
+}
+
+/// Index of the cell `encode` puts `v` in, along an axis starting at `min`
+/// and spanning `extent` degrees split into `cells` cells.
+fn grid_index(v: f64, min: f64, extent: f64, cells: u64) -> u64 {
+    let index = ((v - min) / extent * cells as f64).ceil() - 1f64;
+    if index < 0f64 {
+        0
+    } else {
+        (index as u64).min(cells - 1)
+    }
+}
+
+/// Remove from the cells in `a` the area covered by the cells in `b`.
+///
+/// Cells nest: a hash covers every longer hash it is a prefix of. So a cell
+/// of `a` is dropped when some cell of `b` is a prefix of it, and a cell of
+/// `a` that only partly overlaps finer cells of `b` is split into its 16
+/// children, recursively, keeping those the finer cells leave uncovered.
+/// The result is sorted and free of duplicates.
+///
+/// ### Examples
+///
+/// ```rust
+/// let diff = geohash::difference(&["e7", "e8", "e90"], &["e9", "e71", "e8"]);
+///
+/// assert_eq!(
+///     diff,
+///     vec![
+///         "e70", "e72", "e73", "e74", "e75", "e76", "e77", "e78", "e79", "e7a", "e7b", "e7c",
+///         "e7d", "e7e", "e7f",
+///     ]
+/// );
+/// ```
+pub fn difference(a: &[&str], b: &[&str]) -> Vec<String> {
+    let mut out = Vec::new();
+    for &cell in a {
+        subtract(cell.to_owned(), b, &mut out);
+    }
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// Group geohashes under their length `target_len` prefix, the coarser cell
+/// each of them lies in.
+///
+/// Every hash is checked for valid characters, and one shorter than
+/// `target_len` has no such prefix and is an error. Groups are keyed by the
+/// lowercase prefix, so hashes differing only in case share a group. Within
+/// a group, hashes keep their order in `hashes`, duplicates included.
+///
+/// ### Examples
+///
+/// ```rust
+/// let groups = geohash::downsample(&["e71150dc9", "e71150aa0", "e7120"], 5)
+///     .expect("Invalid hash string");
+///
+/// assert_eq!(groups["e7115"], vec!["e71150dc9", "e71150aa0"]);
+/// assert_eq!(groups["e7120"], vec!["e7120"]);
+/// ```
+pub fn downsample(
+    hashes: &[&str],
+    target_len: usize,
+) -> Result<HashMap<String, Vec<String>>, Error> {
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    for hash in hashes {
+        if hash.len() < target_len {
+            bail!(GeohashError::InvalidLength { len: hash.len() });
+        }
+        for c in hash.chars() {
+            hash_value_of_char(c)?;
+        }
+        groups
+            .entry(hash[..target_len].to_ascii_lowercase())
+            .or_default()
+            .push(hash.to_string());
+    }
+    Ok(groups)
+}
+
+/// Compute the area shared by the cells in `a` and the cells in `b`.
+///
+/// Two cells overlap only when one is a prefix of the other, and their
+/// shared area is then the longer, finer one. Cells already covered by a
+/// coarser cell of the result are left out, and the result is sorted and
+/// free of duplicates.
+///
+/// ### Examples
+///
+/// ```rust
+/// let common = geohash::intersection(&["e7", "e8"], &["e71", "e7f", "e9", "e"]);
+///
+/// assert_eq!(common, vec!["e7", "e8"]);
+///
+/// let common = geohash::intersection(&["e7", "e8"], &["e71", "e7f", "e9"]);
+///
+/// assert_eq!(common, vec!["e71", "e7f"]);
+/// ```
+pub fn intersection(a: &[&str], b: &[&str]) -> Vec<String> {
+    let mut out = Vec::new();
+    for &x in a {
+        for &y in b {
+            if y.starts_with(x) {
+                out.push(y.to_owned());
+            } else if x.starts_with(y) {
+                out.push(x.to_owned());
+            }
+        }
+    }
+    out.sort();
+    out.dedup();
+    // sorted, so a cell's covering prefixes come before it
+    let mut kept: Vec<String> = Vec::with_capacity(out.len());
+    for cell in out {
+        if !kept.iter().any(|coarse| cell.starts_with(coarse.as_str())) {
+            kept.push(cell);
+        }
+    }
+    kept
+}
+
+/// Compute the centroid of the centers of the given cells.
+///
+/// The centers are averaged as unit vectors on the sphere and the mean vector
+/// is projected back to longitude/latitude, so cells on both sides of the
+/// antimeridian average to a point near it rather than near longitude `0`.
+/// If the centers cancel out exactly, as for two antipodal cells, the result
+/// is arbitrary. An empty slice is an error.
+///
+/// ### Examples
+///
+/// ```rust
+/// // "4" and "e" lie on either side of the antimeridian
+/// let c = geohash::centroid(&["4", "e"]).expect("Invalid hash string");
+///
+/// assert!((c.x.abs() - 180.0).abs() < 1e-9);
+/// ```
+pub fn centroid(hashes: &[&str]) -> Result<Coordinate<f64>, Error> {
+    if hashes.is_empty() {
+        bail!(GeohashError::EmptyInput);
+    }
+    mean_center(hashes.iter().map(|&hash| (hash, 1f64)))
+}
+
+/// Compute the weighted centroid of the centers of the given cells, as a
+/// geohash with length `len`, for summarizing a heatmap of `(hash, weight)`
+/// entries.
+///
+/// The centers are averaged on the sphere as in `centroid`, each weighed by
+/// its entry's weight. Weights must be finite and non-negative, and an empty
+/// slice or a total weight of zero is an error.
+///
+/// ### Examples
+///
+/// ```rust
+/// let entries = [("e71150", 3.0), ("e71151", 1.0), ("e71154", 0.0)];
+///
+/// let hash = geohash::weighted_centroid(&entries, 6).expect("Invalid entries");
+///
+/// assert_eq!(hash, "e71150");
+/// ```
+pub fn weighted_centroid(entries: &[(&str, f64)], len: usize) -> Result<String, Error> {
+    if entries.is_empty() {
+        bail!(GeohashError::EmptyInput);
+    }
+    let mut total = 0f64;
+    for &(_, weight) in entries {
+        if !weight.is_finite() || weight < 0f64 {
+            bail!(GeohashError::InvalidWeight { weight });
+        }
+        total += weight;
+    }
+    if total == 0f64 {
+        bail!(GeohashError::ZeroWeight);
+    }
+    encode(mean_center(entries.iter().cloned())?, len)
+}
+
+/// Average the centers of the given cells as unit vectors, each scaled by
+/// its weight, and project the mean back to longitude/latitude.
+fn mean_center<'a, I: Iterator<Item = (&'a str, f64)>>(
+    entries: I,
+) -> Result<Coordinate<f64>, Error> {
+    let (mut x, mut y, mut z) = (0f64, 0f64, 0f64);
+    for (hash, weight) in entries {
+        let (c, _, _) = decode(hash)?;
+        let (lon, lat) = (c.x.to_radians(), c.y.to_radians());
+        x += weight * lat.cos() * lon.cos();
+        y += weight * lat.cos() * lon.sin();
+        z += weight * lat.sin();
+    }
+    Ok(Coordinate {
+        x: y.atan2(x).to_degrees(),
+        y: z.atan2(x.hypot(y)).to_degrees(),
+    })
+}
+
+/// Find the smallest cell containing all the given coordinates.
+///
+/// Every coordinate is encoded at 16 characters, the useful limit of `f64`,
+/// and the result is their longest common prefix. Points on both sides of a
+/// top-level cell boundary, such as the equator or the prime meridian, share
+/// no prefix and give the empty hash, which stands for the whole world. An
+/// empty slice is an error.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coords = [
+///     geohash::Coordinate { x: 112.5584, y: 37.8324 },
+///     geohash::Coordinate { x: 112.5123, y: 37.8001 },
+/// ];
+///
+/// let hash = geohash::bounding_geohash(&coords).expect("Invalid coordinates");
+///
+/// assert_eq!(hash, "e71150");
+/// ```
+pub fn bounding_geohash(coords: &[Coordinate<f64>]) -> Result<String, Error> {
+    let mut prefix = match coords.first() {
+        Some(&c) => encode(c, MAX_GRID_LEN)?,
+        None => bail!(GeohashError::EmptyInput),
+    };
+    for &c in &coords[1..] {
+        let hash = encode(c, MAX_GRID_LEN)?;
+        let common = shared_prefix_len(&prefix, &hash);
+        prefix.truncate(common);
+    }
+    Ok(prefix)
+}
+
+/// Find the smallest cell containing both of the given cells entirely.
+///
+/// Cells nest, a cell holding exactly the cells it is a prefix of, so this is
+/// the longest common prefix of the two hashes, in lowercase; see
+/// `common_prefix_len`. It can be finer than `bounding_geohash` of the two
+/// cells' corners: a point on a cell's south or west edge encodes to the
+/// neighbor beyond it, whose hash may share less of the prefix. Both hashes
+/// are validated.
+///
+/// ### Examples
+///
+/// ```rust
+/// let cell = geohash::enclosing_cell("e71150", "e7114f").expect("Invalid hash string");
+///
+/// assert_eq!(cell, "e711");
+/// ```
+pub fn enclosing_cell(a: &str, b: &str) -> Result<String, Error> {
+    let len = common_prefix_len(a, b)?;
+    Ok(a[..len].to_lowercase())
+}
+
+fn subtract(cell: String, b: &[&str], out: &mut Vec<String>) {
+    if b.iter().any(|hash| cell.starts_with(hash)) {
+        return;
+    }
+    if b.iter().any(|hash| hash.starts_with(&cell)) {
+        for &code in BASE32_CODES {
+            let mut child = cell.clone();
+            child.push(code);
+            subtract(child, b, out);
+        }
+    } else {
+        out.push(cell);
+    }
+}
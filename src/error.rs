@@ -1,9 +1,65 @@
-use crate::Coordinate;
+// `failure_derive` emits its impls inside an anonymous const.
+#![allow(non_local_definitions)]
+
+use crate::{Coordinate, Direction};
+
+use failure::Error;
 
 #[derive(Debug, Fail)]
 pub enum GeohashError {
     #[fail(display = "invalid hash character: {}", character)]
     InvalidHashCharacter { character: char },
+    #[fail(
+        display = "invalid hash character: {} is a base32 geohash character, this crate uses base16",
+        character
+    )]
+    Base32HashCharacter { character: char },
     #[fail(display = "invalid coordinate range: {:?}", c)]
     InvalidCoordinateRange { c: Coordinate<f64> },
+    #[fail(display = "invalid path index: {}", index)]
+    InvalidPathIndex { index: u8 },
+    #[fail(display = "invalid hash length: {}", len)]
+    InvalidLength { len: usize },
+    #[fail(display = "invalid bit depth: {}", bits)]
+    InvalidBitDepth { bits: usize },
+    #[fail(display = "hash too long: {} characters, at most {} allowed", len, max)]
+    HashTooLong { len: usize, max: usize },
+    #[fail(display = "invalid shared prefix length: {}", len)]
+    InvalidSharedPrefix { len: usize },
+    #[fail(display = "truncated record: {} of {} bytes", len, expected)]
+    TruncatedRecord { len: usize, expected: usize },
+    #[fail(display = "invalid record at byte {}", offset)]
+    InvalidRecord { offset: usize },
+    #[fail(display = "hash lengths differ: {} and {}", left, right)]
+    LengthMismatch { left: usize, right: usize },
+    #[fail(display = "empty input")]
+    EmptyInput,
+    #[fail(
+        display = "invalid weight: {}, expected a finite non-negative value",
+        weight
+    )]
+    InvalidWeight { weight: f64 },
+    #[fail(
+        display = "invalid radius: {}, expected a finite non-negative distance in meters",
+        radius
+    )]
+    InvalidRadius { radius: f64 },
+    #[fail(display = "total weight is zero")]
+    ZeroWeight,
+    #[fail(display = "no {:?} neighbor beyond the pole", direction)]
+    NeighborBeyondPole { direction: Direction },
+    #[fail(
+        display = "invalid cell offset: {}, expected a value in [0, 1]",
+        offset
+    )]
+    InvalidOffset { offset: f64 },
+    #[fail(display = "invalid GeoJSON geometry: {}", reason)]
+    InvalidGeoJson { reason: String },
+    #[fail(
+        display = "grid coordinates out of range: ({}, {}) at length {}",
+        col, row, len
+    )]
+    InvalidGridCoords { col: u32, row: u32, len: usize },
+    #[fail(display = "invalid input at index {}: {}", index, error)]
+    InvalidAtIndex { index: usize, error: Error },
 }
@@ -0,0 +1,143 @@
+use crate::core::{decode, encode, neighbors};
+use crate::{Coordinate, Neighbors, Point, Rect};
+
+use failure::Error;
+
+/// Encode coordinates with method syntax, see `encode`.
+///
+/// ### Examples
+///
+/// ```rust
+/// use geohash::GeohashExt;
+///
+/// let coord = geohash::Coordinate { x: 112.5584f64, y: 37.8324f64 };
+///
+/// assert_eq!(coord.geohash(9).expect("Invalid coordinate"), "e71150dc9");
+/// assert_eq!(geohash::Point(coord).geohash(9).unwrap(), "e71150dc9");
+/// ```
+pub trait GeohashExt {
+    /// Encode to a geohash with length `len`.
+    fn geohash(&self, len: usize) -> Result<String, Error>;
+}
+
+impl GeohashExt for Coordinate<f64> {
+    fn geohash(&self, len: usize) -> Result<String, Error> {
+        encode(*self, len)
+    }
+}
+
+impl GeohashExt for Point<f64> {
+    fn geohash(&self, len: usize) -> Result<String, Error> {
+        encode(self.0, len)
+    }
+}
+
+/// Decode geohashes with method syntax, see `decode` and `neighbors`.
+///
+/// ### Examples
+///
+/// ```rust
+/// use geohash::GeohashStrExt;
+///
+/// let (c, _, _) = "e71150dc9".decode().expect("Invalid hash string");
+///
+/// assert!((c.x - 112.5584).abs() < 1e-3);
+/// assert_eq!("e71150dc9".neighbors().unwrap().e, "e71150dcb");
+/// ```
+pub trait GeohashStrExt {
+    /// Decode to the cell's center and the `(longitude, latitude)` error
+    /// margins.
+    fn decode(&self) -> Result<(Coordinate<f64>, f64, f64), Error>;
+
+    /// Find all neighboring geohashes.
+    fn neighbors(&self) -> Result<Neighbors, Error>;
+}
+
+impl GeohashStrExt for str {
+    fn decode(&self) -> Result<(Coordinate<f64>, f64, f64), Error> {
+        decode(self)
+    }
+
+    fn neighbors(&self) -> Result<Neighbors, Error> {
+        neighbors(self)
+    }
+}
+
+/// Methods on `Rect`: conversion to and from a plain `[f64; 4]`, for compact
+/// fixed-layout storage without serde, and growing by a margin.
+///
+/// The array holds `[min.x, min.y, max.x, max.y]`, that is `[min_lon,
+/// min_lat, max_lon, max_lat]`, the order of a GeoJSON `bbox`. No check is
+/// made that `min` lies south-west of `max`.
+///
+/// ### Examples
+///
+/// ```rust
+/// use geohash::RectExt;
+///
+/// let rect = geohash::decode_bbox("c").expect("Invalid hash string");
+///
+/// assert_eq!(rect.to_array(), [0.0, 0.0, 90.0, 45.0]);
+/// assert_eq!(geohash::Rect::from_array(rect.to_array()), rect);
+/// ```
+pub trait RectExt {
+    /// The corners as `[min.x, min.y, max.x, max.y]`.
+    fn to_array(&self) -> [f64; 4];
+
+    /// Build a rect from corners laid out as `[min.x, min.y, max.x, max.y]`.
+    fn from_array(array: [f64; 4]) -> Self;
+
+    /// Grow the rect by `dx` degrees of longitude and `dy` degrees of
+    /// latitude on each side.
+    ///
+    /// Latitudes are clamped to `±90`. Longitudes are clamped to `±180` as
+    /// well rather than wrapped: a `Rect` cannot span the antimeridian, so a
+    /// buffer reaching past it is cut off there. Negative margins shrink the
+    /// rectangle.
+    ///
+    /// ### Examples
+    ///
+    /// ```rust
+    /// use geohash::RectExt;
+    ///
+    /// let rect = geohash::decode_bbox("c").expect("Invalid hash string");
+    ///
+    /// let buffered = rect.buffer(10.0, 50.0);
+    ///
+    /// assert_eq!((buffered.min.x, buffered.min.y), (-10f64, -50f64));
+    /// assert_eq!((buffered.max.x, buffered.max.y), (100f64, 90f64));
+    /// ```
+    fn buffer(&self, dx: f64, dy: f64) -> Self;
+}
+
+impl RectExt for Rect<f64> {
+    fn to_array(&self) -> [f64; 4] {
+        [self.min.x, self.min.y, self.max.x, self.max.y]
+    }
+
+    fn from_array(array: [f64; 4]) -> Rect<f64> {
+        Rect {
+            min: Coordinate {
+                x: array[0],
+                y: array[1],
+            },
+            max: Coordinate {
+                x: array[2],
+                y: array[3],
+            },
+        }
+    }
+
+    fn buffer(&self, dx: f64, dy: f64) -> Rect<f64> {
+        Rect {
+            min: Coordinate {
+                x: (self.min.x - dx).max(-180f64),
+                y: (self.min.y - dy).max(-90f64),
+            },
+            max: Coordinate {
+                x: (self.max.x + dx).min(180f64),
+                y: (self.max.y + dy).min(90f64),
+            },
+        }
+    }
+}
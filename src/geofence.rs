@@ -0,0 +1,84 @@
+use std::collections::HashSet;
+
+use crate::core::{encode, hash_value_of_char};
+use crate::coverage::{cover_bbox, cover_polygon};
+use crate::{Coordinate, Rect};
+
+use failure::Error;
+
+/// A region precomputed as a set of geohash cells, for testing many points
+/// against it.
+///
+/// Membership is decided at the cells' resolution, so points slightly outside
+/// the original region but inside one of its edge cells are reported as
+/// contained.
+///
+/// ### Examples
+///
+/// ```rust
+/// let rect = geohash::Rect {
+///     min: geohash::Coordinate { x: 112.0, y: 37.5 },
+///     max: geohash::Coordinate { x: 113.0, y: 38.0 },
+/// };
+///
+/// let fence = geohash::Geofence::from_rect(&rect, 5).expect("Invalid rect");
+///
+/// assert!(fence.contains(geohash::Coordinate { x: 112.5584, y: 37.8324 }));
+/// assert!(!fence.contains(geohash::Coordinate { x: -120.6623, y: 35.3003 }));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Geofence {
+    cells: HashSet<String>,
+    max_len: usize,
+}
+
+impl Geofence {
+    /// Build a geofence from arbitrary cells, possibly of mixed lengths.
+    ///
+    /// Cells are validated and stored in lowercase, so either case matches.
+    /// The empty cell `""` stands for the whole world.
+    pub fn from_cells<I: IntoIterator<Item = String>>(cells: I) -> Result<Geofence, Error> {
+        let mut set = HashSet::new();
+        for cell in cells {
+            for c in cell.chars() {
+                hash_value_of_char(c)?;
+            }
+            set.insert(cell.to_lowercase());
+        }
+        let max_len = set.iter().map(|cell| cell.len()).max().unwrap_or(0);
+        Ok(Geofence {
+            cells: set,
+            max_len,
+        })
+    }
+
+    /// Build a geofence from the length `len` cells covering `rect`, see
+    /// `cover_bbox`.
+    pub fn from_rect(rect: &Rect<f64>, len: usize) -> Result<Geofence, Error> {
+        Geofence::from_cells(cover_bbox(rect, len)?)
+    }
+
+    /// Build a geofence from the length `len` cells covering the polygon with
+    /// the given vertices, see `cover_polygon`.
+    pub fn from_polygon(vertices: &[Coordinate<f64>], len: usize) -> Result<Geofence, Error> {
+        Geofence::from_cells(cover_polygon(vertices, len)?)
+    }
+
+    /// The cells making up the geofence.
+    pub fn cells(&self) -> &HashSet<String> {
+        &self.cells
+    }
+
+    /// Check whether `c` falls in one of the geofence's cells. Coordinates
+    /// outside the valid range are never contained.
+    pub fn contains(&self, c: Coordinate<f64>) -> bool {
+        if self.cells.is_empty() {
+            return false;
+        }
+        // A fence of only the world cell still needs a hash to validate `c`
+        match encode(c, self.max_len.max(1)) {
+            Ok(hash) => (0..=self.max_len).any(|len| self.cells.contains(&hash[..len])),
+            Err(_) => false,
+        }
+    }
+}
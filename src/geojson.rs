@@ -0,0 +1,89 @@
+use serde_json::Value;
+
+use crate::coverage::{cover_polygon, cover_polygon_outline};
+use crate::{Coordinate, GeohashError};
+
+use failure::Error;
+
+/// List the length `len` geohashes of every cell overlapping a GeoJSON
+/// `Polygon` or `MultiPolygon` geometry. Requires the `geojson` feature.
+///
+/// Each polygon is covered with `cover_polygon`, and cells lying entirely
+/// inside one of its holes are then removed. Cells along a hole's outline
+/// overlap the polygon and are kept. Positions are read as
+/// `[longitude, latitude]`, as GeoJSON specifies. The result is sorted and
+/// free of duplicates. Malformed JSON and other geometry types are errors.
+///
+/// ### Examples
+///
+/// ```rust
+/// # #[cfg(feature = "geojson")]
+/// # {
+/// let geometry = r#"{
+///     "type": "Polygon",
+///     "coordinates": [[[-100, -50], [100, -50], [100, 50], [-100, 50], [-100, -50]]]
+/// }"#;
+///
+/// let cells = geohash::cover_geojson(geometry, 1).expect("Invalid geometry");
+///
+/// assert_eq!(cells.len(), 16);
+/// # }
+/// ```
+pub fn cover_geojson(geometry: &str, len: usize) -> Result<Vec<String>, Error> {
+    let value: Value = serde_json::from_str(geometry)?;
+    let polygons = match (value["type"].as_str(), &value["coordinates"]) {
+        (Some("Polygon"), coordinates) => vec![coordinates],
+        (Some("MultiPolygon"), Value::Array(polygons)) => polygons.iter().collect(),
+        (Some("MultiPolygon"), _) => invalid("MultiPolygon coordinates must be an array")?,
+        (Some(kind), _) => invalid(&format!("unsupported geometry type {}", kind))?,
+        (None, _) => invalid("missing geometry type")?,
+    };
+
+    let mut out = vec![];
+    for polygon in polygons {
+        let rings = match polygon {
+            Value::Array(rings) if !rings.is_empty() => rings,
+            _ => invalid("a polygon must be a non-empty array of rings")?,
+        };
+        let mut cells = cover_polygon(&ring_vertices(&rings[0])?, len)?;
+        for hole in &rings[1..] {
+            let hole = ring_vertices(hole)?;
+            let edge = cover_polygon_outline(&hole, len)?;
+            let inside: Vec<String> = cover_polygon(&hole, len)?
+                .into_iter()
+                .filter(|cell| edge.binary_search(cell).is_err())
+                .collect();
+            cells.retain(|cell| inside.binary_search(cell).is_err());
+        }
+        out.extend(cells);
+    }
+    out.sort();
+    out.dedup();
+    Ok(out)
+}
+
+/// Read a GeoJSON linear ring as polygon vertices, dropping the closing
+/// position that repeats the first one.
+fn ring_vertices(ring: &Value) -> Result<Vec<Coordinate<f64>>, Error> {
+    let positions = match ring {
+        Value::Array(positions) => positions,
+        _ => invalid("a ring must be an array of positions")?,
+    };
+    let mut vertices = vec![];
+    for position in positions {
+        match (position[0].as_f64(), position[1].as_f64()) {
+            (Some(x), Some(y)) => vertices.push(Coordinate { x, y }),
+            _ => invalid("a position must hold a longitude and a latitude")?,
+        }
+    }
+    if vertices.len() > 1 && vertices.first() == vertices.last() {
+        vertices.pop();
+    }
+    Ok(vertices)
+}
+
+fn invalid<T>(reason: &str) -> Result<T, Error> {
+    bail!(GeohashError::InvalidGeoJson {
+        reason: reason.to_string(),
+    });
+}
@@ -0,0 +1,73 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Deref;
+
+use crate::core::hash_value_of_char;
+
+use failure::Error;
+
+/// A geohash string known to hold only valid characters.
+///
+/// Build one with `TryFrom`, which checks every character the way `decode`
+/// does and stores the hash in lowercase, so hashes naming the same cell
+/// compare and hash equal whatever their case. Then pass it wherever a `&str` geohash is expected: it derefs to
+/// `str` and implements `AsRef<str>`.
+///
+/// ### Examples
+///
+/// ```rust
+/// use std::convert::TryFrom;
+///
+/// let hash = geohash::Geohash::try_from("e71150").expect("Invalid hash string");
+///
+/// assert_eq!(geohash::decode_bbox(&hash).unwrap(), geohash::decode_bbox("e71150").unwrap());
+/// assert!(geohash::Geohash::try_from("e711z").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Geohash(String);
+
+impl Geohash {
+    /// Unwrap the underlying string.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl TryFrom<String> for Geohash {
+    type Error = Error;
+
+    fn try_from(hash: String) -> Result<Geohash, Error> {
+        for c in hash.chars() {
+            hash_value_of_char(c)?;
+        }
+        Ok(Geohash(hash.to_lowercase()))
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Geohash {
+    type Error = Error;
+
+    fn try_from(hash: &'a str) -> Result<Geohash, Error> {
+        Geohash::try_from(hash.to_string())
+    }
+}
+
+impl AsRef<str> for Geohash {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Geohash {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Geohash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::core::encode;
+use crate::coverage::hash_ranges;
+use crate::{Coordinate, GeohashError, Rect};
+
+use failure::Error;
+
+/// An in-memory spatial index of values keyed by the geohash of their
+/// coordinate, kept in a `BTreeMap` so a bounding box query becomes a few
+/// range scans, see `hash_ranges`.
+///
+/// Values may be inserted at different lengths; each length present costs
+/// its own set of range scans per query. Several values may share a cell.
+///
+/// ### Examples
+///
+/// ```rust
+/// let mut index = geohash::GeohashIndex::new();
+/// index.insert(geohash::Coordinate { x: 112.5584, y: 37.8324 }, 6, "taiyuan").unwrap();
+/// index.insert(geohash::Coordinate { x: -120.6623, y: 35.3003 }, 6, "slo").unwrap();
+///
+/// let rect = geohash::Rect {
+///     min: geohash::Coordinate { x: 112.0, y: 37.5 },
+///     max: geohash::Coordinate { x: 113.0, y: 38.0 },
+/// };
+/// let found = index.query_bbox(&rect).unwrap();
+///
+/// assert_eq!(found, vec![("e71150", &"taiyuan")]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GeohashIndex<V> {
+    entries: BTreeMap<String, Vec<(Coordinate<f64>, V)>>,
+    lens: BTreeSet<usize>,
+}
+
+impl<V> GeohashIndex<V> {
+    /// Create an empty index.
+    pub fn new() -> GeohashIndex<V> {
+        GeohashIndex {
+            entries: BTreeMap::new(),
+            lens: BTreeSet::new(),
+        }
+    }
+
+    /// Add `value` at `c`, keyed by its length `len` geohash. Lengths outside
+    /// `1..=16` are rejected, since `query_bbox` could not scan them.
+    pub fn insert(&mut self, c: Coordinate<f64>, len: usize, value: V) -> Result<(), Error> {
+        if len == 0 || len > 16 {
+            bail!(GeohashError::InvalidLength { len });
+        }
+        let hash = encode(c, len)?;
+        self.entries.entry(hash).or_default().push((c, value));
+        self.lens.insert(len);
+        Ok(())
+    }
+
+    /// Find the values whose coordinates lie inside `rect`, edges included,
+    /// with the geohash each is keyed by, in geohash order.
+    pub fn query_bbox(&self, rect: &Rect<f64>) -> Result<Vec<(&str, &V)>, Error> {
+        let mut out = vec![];
+        for &len in &self.lens {
+            for (lo, hi) in hash_ranges(rect, len)? {
+                for (hash, values) in self.entries.range(lo..=hi) {
+                    if hash.len() != len {
+                        continue;
+                    }
+                    for (c, value) in values {
+                        if c.x >= rect.min.x
+                            && c.x <= rect.max.x
+                            && c.y >= rect.min.y
+                            && c.y <= rect.max.y
+                        {
+                            out.push((hash.as_str(), value));
+                        }
+                    }
+                }
+            }
+        }
+        out.sort_by(|a, b| a.0.cmp(b.0));
+        Ok(out)
+    }
+
+    /// Number of values in the index.
+    pub fn len(&self) -> usize {
+        self.entries.values().map(Vec::len).sum()
+    }
+
+    /// Whether the index holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<V> Default for GeohashIndex<V> {
+    fn default() -> GeohashIndex<V> {
+        GeohashIndex::new()
+    }
+}
@@ -1,4 +1,5 @@
 #![doc(html_root_url = "https://docs.rs/geohash/")]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
 
 //! # Geohash
 //!
@@ -7,6 +8,11 @@
 //! You can find more about the original geohash algorithm on [Wikipedia](https://en.wikipedia.org/wiki/Geohash)
 //! This crate provides an alternative base16 encoded version
 //!
+//! Each character carries 4 bits instead of the 5 of a standard base32
+//! geohash, so hashes from the two schemes are not interchangeable and their
+//! lengths do not map 1:1: `n` characters here hold `4 * n` bits, see
+//! [`bits_of`](fn.bits_of.html).
+//!
 //! ## Usage
 //! ```rust
 //! extern crate geohash;
@@ -33,16 +39,99 @@
 //!
 
 extern crate geo_types;
-#[cfg(test)]
 extern crate num_traits;
 #[macro_use]
 extern crate failure;
+#[cfg(feature = "arrayvec")]
+extern crate arrayvec;
+#[cfg(feature = "rand")]
+extern crate rand;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
+#[cfg(feature = "prost")]
+extern crate prost;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "geojson")]
+extern crate serde_json;
 
+mod batch;
+mod bits;
+mod components;
+mod coord;
 mod core;
+mod coverage;
 mod error;
+mod ext;
+mod geofence;
+#[cfg(feature = "geojson")]
+mod geojson;
+mod hash;
+mod index;
+mod map;
 mod neighbors;
+mod pack;
+mod precision;
+#[cfg(feature = "prost")]
+mod proto;
+mod reader;
+mod rect;
+mod render;
+mod table;
+mod track;
+mod walker;
 
-pub use crate::core::{decode, decode_bbox, encode, neighbor, neighbors};
+pub use crate::batch::encode_batch_simd;
+pub use crate::bits::{
+    bit_diff, bits_of, common_prefix_len, encode_bits, from_grid_coords, from_index_path,
+    grid_coords, hilbert_index, int_range, split_at, to_h3_like_path, to_quadkey,
+};
+pub use crate::components::{connected_components, Connectivity};
+#[cfg(feature = "rand")]
+pub use crate::coord::random_point_in;
+pub use crate::coord::{
+    antipode, decode_normalized, grid_point_hash, interpolate_cells, normalize, snap, snap_all,
+};
+pub use crate::core::{
+    cell_bounds_exact, center, corner_distances, decode, decode_bbox, decode_bbox_bytes,
+    decode_bbox_with_bounds, decode_bounds, decode_f32, decode_lat_lon, decode_with_axis_order,
+    decode_with_max_len, decode_with_offset, edge_distances, encode, encode_f32, encode_iter,
+    encode_lat_lon, encode_stable, encode_unchecked, encode_uppercase, encode_with_axis_order,
+    encode_with_bounds, encode_with_mode, encode_with_offset, is_stable, neighbor, neighbor_fn,
+    neighbors, neighbors_all, validate_batch, with_neighbors, AxisOrder, EncodeMode,
+};
+#[cfg(feature = "arrayvec")]
+pub use crate::core::{encode_small, SMALL_HASH_CAPACITY};
+#[cfg(feature = "rayon")]
+pub use crate::coverage::cover_bbox_parallel;
+pub use crate::coverage::{
+    bounding_geohash, cell_circle_overlap, centroid, count_cells, cover_bbox, cover_buffered_path,
+    cover_circle_weighted, cover_polygon, cover_polygon_outline, difference, downsample,
+    enclosing_cell, grid, hash_range, hash_ranges, intersection, tiles_aligned, weighted_centroid,
+};
 pub use crate::error::GeohashError;
-pub use crate::neighbors::{Direction, Neighbors};
-pub use geo_types::{Coordinate, Rect};
+pub use crate::ext::{GeohashExt, GeohashStrExt, RectExt};
+pub use crate::geofence::Geofence;
+#[cfg(feature = "geojson")]
+pub use crate::geojson::cover_geojson;
+pub use crate::hash::Geohash;
+pub use crate::index::GeohashIndex;
+pub use crate::map::GeohashMap;
+pub use crate::neighbors::{
+    adjacency, adjacency_mask, is_edge_cell, neighbors_at_precision, ring, spiral, Direction,
+    Neighbors,
+};
+pub use crate::pack::{pack_coverage, unpack_coverage};
+#[cfg(feature = "std")]
+pub use crate::precision::describe;
+pub use crate::precision::{bearing, cell_dimensions, length_for_zoom, quantization_error};
+#[cfg(feature = "prost")]
+pub use crate::proto::{to_proto, GeohashCell};
+pub use crate::reader::decode_reader;
+pub use crate::rect::quadrants;
+pub use crate::render::{ascii_map, color_for};
+pub use crate::table::NEIGHBOR_TABLE_MAX_LEN;
+pub use crate::track::{decode_track, encode_track};
+pub use crate::walker::GridWalker;
+pub use geo_types::{Coordinate, Point, Rect};
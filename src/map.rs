@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use crate::core::{decode, encode, neighbor_of};
+use crate::{Coordinate, Direction};
+
+use failure::Error;
+
+/// A map from geohash cells of a fixed length to values, looked up by
+/// coordinate.
+///
+/// Each cell holds at most one value, keyed by the length `hash_len` geohash
+/// of the coordinates it was inserted at; a later insert into the same cell
+/// replaces it. `get_with_neighbors` also reads the 8 cells around a
+/// coordinate's, the usual first step of a proximity lookup.
+///
+/// ### Examples
+///
+/// ```rust
+/// let mut map = geohash::GeohashMap::new(6);
+/// map.insert(geohash::Coordinate { x: 112.5584, y: 37.8324 }, "taiyuan").unwrap();
+///
+/// let nearby = geohash::Coordinate { x: 112.56, y: 37.83 };
+///
+/// assert_eq!(map.get(nearby).unwrap(), Some(&"taiyuan"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct GeohashMap<V> {
+    hash_len: usize,
+    cells: HashMap<String, V>,
+}
+
+impl<V> GeohashMap<V> {
+    /// Create an empty map keyed by length `hash_len` geohashes.
+    pub fn new(hash_len: usize) -> GeohashMap<V> {
+        GeohashMap {
+            hash_len,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Length of the geohashes the map is keyed by.
+    pub fn hash_len(&self) -> usize {
+        self.hash_len
+    }
+
+    /// Store `value` in the cell of `c`, returning the value it replaces.
+    pub fn insert(&mut self, c: Coordinate<f64>, value: V) -> Result<Option<V>, Error> {
+        let hash = encode(c, self.hash_len)?;
+        Ok(self.cells.insert(hash, value))
+    }
+
+    /// Find the value in the cell of `c`.
+    pub fn get(&self, c: Coordinate<f64>) -> Result<Option<&V>, Error> {
+        let hash = encode(c, self.hash_len)?;
+        Ok(self.cells.get(&hash))
+    }
+
+    /// Find the values in the cell of `c` and its 8 neighbors, with the
+    /// geohash of the cell each is in. The cell of `c` comes first, then its
+    /// neighbors clockwise from north, see `Direction::ALL`; empty cells and
+    /// neighbors past the poles or the antimeridian are left out.
+    pub fn get_with_neighbors(&self, c: Coordinate<f64>) -> Result<Vec<(String, &V)>, Error> {
+        let hash = encode(c, self.hash_len)?;
+        let decoded = decode(&hash)?;
+        let mut hashes = vec![hash];
+        for &direction in Direction::ALL.iter() {
+            if let Ok(neighbor) = neighbor_of(decoded, self.hash_len, direction) {
+                hashes.push(neighbor);
+            }
+        }
+        Ok(hashes
+            .into_iter()
+            .filter_map(|hash| {
+                let value = self.cells.get(&hash)?;
+                Some((hash, value))
+            })
+            .collect())
+    }
+
+    /// Number of cells holding a value.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Whether the map holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
@@ -1,3 +1,11 @@
+use std::collections::HashSet;
+
+use crate::bits::{grid_from_hash, hash_from_grid};
+use crate::core::{decode, decode_bbox, encode, neighbor_of, neighbors};
+use crate::{Coordinate, GeohashError};
+
+use failure::Error;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Neighbors {
     pub sw: String,
@@ -31,6 +39,26 @@ pub enum Direction {
 }
 
 impl Direction {
+    /// All directions, clockwise from north. A direction's position in this
+    /// array is its bit in `adjacency_mask`.
+    pub const ALL: [Direction; 8] = [
+        Direction::N,
+        Direction::NE,
+        Direction::E,
+        Direction::SE,
+        Direction::S,
+        Direction::SW,
+        Direction::W,
+        Direction::NW,
+    ];
+
+    /// The bit standing for this direction in `adjacency_mask`: `N` is
+    /// `0b0000_0001`, `NE` is `0b0000_0010`, and so on clockwise up to `NW`,
+    /// `0b1000_0000`.
+    pub fn bit(self) -> u8 {
+        1 << (self as u8)
+    }
+
     pub fn to_tuple(self) -> (f64, f64) {
         match self {
             Direction::SW => (-1f64, -1f64),
@@ -44,3 +72,263 @@ impl Direction {
         }
     }
 }
+
+/// Find the cells `k` steps away from the given geohash: the square ring of
+/// `8 * k` same-length cells around it, or just the cell itself for `k = 0`.
+///
+/// Cells that would lie past the poles or the antimeridian are left out
+/// rather than wrapped. The ring is listed row by row from its south-west
+/// corner.
+///
+/// ### Examples
+///
+/// ```rust
+/// let ring = geohash::ring("e71150dc99", 1).expect("Invalid hash string");
+///
+/// assert_eq!(
+///     ring,
+///     vec![
+///         "e71150dc92", "e71150dc98", "e71150dc9a", "e71150dc93", "e71150dc9b", "e71150dc96",
+///         "e71150dc9c", "e71150dc9e",
+///     ]
+/// );
+/// ```
+pub fn ring(hash_str: &str, k: u32) -> Result<Vec<String>, Error> {
+    let (center, lon_err, lat_err) = decode(hash_str)?;
+    Ok(ring_around(center, lon_err, lat_err, hash_str.len(), k))
+}
+
+/// Walk outwards from the given geohash: the cell itself, then `ring` 1,
+/// ring 2, and so on, so cells come roughly in order of distance.
+///
+/// The iteration ends once a ring lies entirely outside the world, which at
+/// fine lengths is far beyond anything a caller will pull, so stop taking
+/// cells as soon as the search is satisfied.
+///
+/// ### Examples
+///
+/// ```rust
+/// let cells: Vec<String> = geohash::spiral("e71150dc99")
+///     .expect("Invalid hash string")
+///     .take(9)
+///     .collect();
+///
+/// assert_eq!(cells[0], "e71150dc99");
+/// assert_eq!(&cells[1..], &geohash::ring("e71150dc99", 1).unwrap()[..]);
+/// ```
+pub fn spiral(hash_str: &str) -> Result<impl Iterator<Item = String>, Error> {
+    let (center, lon_err, lat_err) = decode(hash_str)?;
+    let len = hash_str.len();
+    // no ring beyond the grid's width can reach back into the world
+    let max_k = 1u64.checked_shl(2 * len as u32).unwrap_or(u64::MAX);
+    Ok((0..)
+        .take_while(move |&k| u64::from(k) <= max_k)
+        .flat_map(move |k| ring_around(center, lon_err, lat_err, len, k)))
+}
+
+fn ring_around(
+    center: Coordinate<f64>,
+    lon_err: f64,
+    lat_err: f64,
+    len: usize,
+    k: u32,
+) -> Vec<String> {
+    let k = i64::from(k);
+    if k == 0 {
+        return encode(center, len).into_iter().collect();
+    }
+    // Only the border: the bottom row, both ends of each row between, then
+    // the top row, keeping the row by row order
+    let bottom = (-k..=k).map(|dx| (dx, -k));
+    let sides = (1 - k..k).flat_map(|dy| vec![(-k, dy), (k, dy)]);
+    let top = (-k..=k).map(|dx| (dx, k));
+    bottom
+        .chain(sides)
+        .chain(top)
+        .filter_map(|(dx, dy)| {
+            let c = Coordinate {
+                x: center.x + 2f64 * lon_err * dx as f64,
+                y: center.y + 2f64 * lat_err * dy as f64,
+            };
+            encode(c, len).ok()
+        })
+        .collect()
+}
+
+/// Summarize which of the 8 neighbors of a geohash are in `set`, one bit per
+/// direction as given by `Direction::bit`.
+///
+/// Neighbors past the poles do not exist and never set their bit.
+///
+/// ### Examples
+///
+/// ```rust
+/// use std::collections::HashSet;
+///
+/// use geohash::Direction;
+///
+/// let set: HashSet<String> = ["e71150dc9c", "e71150dc9b"].iter().map(|s| s.to_string()).collect();
+///
+/// let mask = geohash::adjacency_mask("e71150dc99", &set).expect("Invalid hash string");
+///
+/// assert_eq!(mask, Direction::N.bit() | Direction::E.bit());
+/// ```
+pub fn adjacency_mask(hash_str: &str, set: &HashSet<String>) -> Result<u8, Error> {
+    let decoded = decode(hash_str)?;
+    let mut mask = 0u8;
+    for &direction in Direction::ALL.iter() {
+        if let Ok(neighbor) = neighbor_of(decoded, hash_str.len(), direction) {
+            if set.contains(&neighbor) {
+                mask |= direction.bit();
+            }
+        }
+    }
+    Ok(mask)
+}
+
+/// Tell where `b` lies relative to `a`: `Some(direction)` when `b` is the
+/// neighbor of `a` in that direction, orthogonal or diagonal, and `None` when
+/// it is not one of the 8 neighbors, including when `b` is `a` itself or of
+/// another length.
+///
+/// Both hashes are validated, and letters compare case insensitively.
+/// Cells are not wrapped past the poles or across the antimeridian, as for
+/// `neighbor`.
+///
+/// ### Examples
+///
+/// ```rust
+/// use geohash::Direction;
+///
+/// assert_eq!(geohash::adjacency("e71150dc99", "e71150dc9c").unwrap(), Some(Direction::N));
+/// assert_eq!(geohash::adjacency("e71150dc99", "e71150dc92").unwrap(), Some(Direction::SW));
+/// assert_eq!(geohash::adjacency("e71150dc99", "e71150dc9f").unwrap(), None);
+/// ```
+pub fn adjacency(a: &str, b: &str) -> Result<Option<Direction>, Error> {
+    let decoded = decode(a)?;
+    decode(b)?;
+    let b = b.to_lowercase();
+    Ok(Direction::ALL.iter().cloned().find(|&direction| {
+        neighbor_of(decoded, a.len(), direction).is_ok_and(|neighbor| neighbor == b)
+    }))
+}
+
+/// Tell whether a geohash cell touches the edge of the world, and on which
+/// side.
+///
+/// Returns `N` or `S` for a cell on the top or bottom row, `E` or `W` for a
+/// cell against the antimeridian, a diagonal for a corner cell touching both,
+/// and `None` for an interior cell. Only the empty hash touches opposite
+/// sides; north and east take precedence for it, giving `NE`.
+///
+/// ### Examples
+///
+/// ```rust
+/// use geohash::Direction;
+///
+/// assert_eq!(geohash::is_edge_cell("0").unwrap(), Some(Direction::SW));
+/// assert_eq!(geohash::is_edge_cell("02").unwrap(), Some(Direction::S));
+/// assert_eq!(geohash::is_edge_cell("3").unwrap(), None);
+/// ```
+pub fn is_edge_cell(hash_str: &str) -> Result<Option<Direction>, Error> {
+    // cell edges are exact binary fractions of the domain, so this is only
+    // slack for the comparison, not for accumulated error
+    const EPSILON: f64 = 1e-12;
+
+    let rect = decode_bbox(hash_str)?;
+    let north = rect.max.y >= 90f64 - EPSILON;
+    let south = rect.min.y <= -90f64 + EPSILON;
+    let east = rect.max.x >= 180f64 - EPSILON;
+    let west = rect.min.x <= -180f64 + EPSILON;
+    Ok(match (north, south, east, west) {
+        (true, _, true, _) => Some(Direction::NE),
+        (true, _, false, true) => Some(Direction::NW),
+        (true, _, false, false) => Some(Direction::N),
+        (false, true, true, _) => Some(Direction::SE),
+        (false, true, false, true) => Some(Direction::SW),
+        (false, true, false, false) => Some(Direction::S),
+        (false, false, true, _) => Some(Direction::E),
+        (false, false, false, true) => Some(Direction::W),
+        (false, false, false, false) => None,
+    })
+}
+
+/// Find the neighbors of a geohash, reported as length `out_len` cells.
+///
+/// Neighbors are found at the length of `hash_str` first, see `neighbors`,
+/// then each is brought to `out_len`:
+///
+/// * When `out_len` is at most the input length, each neighbor is truncated
+///   to the coarser cell containing it. Several directions may then share a
+///   cell, or land in the cell containing `hash_str` itself.
+/// * When `out_len` is longer, each neighbor is replaced by its finer cell
+///   touching `hash_str`: the one in the corner for a diagonal, and for a side
+///   the one across the middle of the shared edge, taken on its west or south
+///   half as `encode` would. Lengths above 16 are then rejected.
+///
+/// An `out_len` of zero is rejected.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coarse = geohash::neighbors_at_precision("e71150dc99", 8).expect("Invalid hash string");
+///
+/// assert_eq!(coarse.n, "e71150dc");
+/// assert_eq!(coarse.e, "e71150dc");
+///
+/// let fine = geohash::neighbors_at_precision("e71150", 7).expect("Invalid hash string");
+///
+/// assert!(fine.n.starts_with(&geohash::neighbors("e71150").unwrap().n));
+/// ```
+pub fn neighbors_at_precision(hash_str: &str, out_len: usize) -> Result<Neighbors, Error> {
+    if out_len == 0 {
+        bail!(GeohashError::InvalidLength { len: out_len });
+    }
+    let found = neighbors(hash_str)?;
+    let len = hash_str.len();
+    if out_len <= len {
+        let cut = |mut hash: String| {
+            hash.truncate(out_len);
+            hash
+        };
+        return Ok(Neighbors {
+            sw: cut(found.sw),
+            s: cut(found.s),
+            se: cut(found.se),
+            w: cut(found.w),
+            e: cut(found.e),
+            nw: cut(found.nw),
+            n: cut(found.n),
+            ne: cut(found.ne),
+        });
+    }
+    if out_len > 16 {
+        bail!(GeohashError::InvalidLength { len: out_len });
+    }
+
+    // Every neighbor exists, so the cell is clear of the world's edges and
+    // none of these underflow or leave the grid
+    let (col, row) = grid_from_hash(hash_str)?;
+    let scale = 1u64 << (2 * (out_len - len));
+    let (west, east, mid_col) = (
+        col * scale - 1,
+        (col + 1) * scale,
+        col * scale + scale / 2 - 1,
+    );
+    let (south, north, mid_row) = (
+        row * scale - 1,
+        (row + 1) * scale,
+        row * scale + scale / 2 - 1,
+    );
+    let at = |col, row| hash_from_grid(col, row, out_len);
+    Ok(Neighbors {
+        sw: at(west, south),
+        s: at(mid_col, south),
+        se: at(east, south),
+        w: at(west, mid_row),
+        e: at(east, mid_row),
+        nw: at(west, north),
+        n: at(mid_col, north),
+        ne: at(east, north),
+    })
+}
@@ -0,0 +1,81 @@
+use crate::bits::to_bits;
+use crate::core::BASE32_CODES;
+use crate::GeohashError;
+
+use failure::Error;
+
+/// Pack a set of geohashes, such as a `cover_bbox` result, into a compact
+/// byte stream.
+///
+/// Each hash becomes one record: a byte holding its length, then its integer
+/// form, the hash read as a hexadecimal number as in `bit_diff`, in the
+/// fewest big-endian bytes holding `4 * len` bits. A record takes
+/// `1 + ceil(len / 2)` bytes, about half the size of the hash as text. Hashes
+/// are validated and limited to 16 characters; an invalid one fails the call
+/// with `GeohashError::InvalidAtIndex`, naming its index. Letters are not kept apart by case, so uppercase hashes unpack
+/// in lowercase.
+///
+/// ### Examples
+///
+/// ```rust
+/// let packed = geohash::pack_coverage(&["e7115", "e7116"]).expect("Invalid hash string");
+///
+/// assert_eq!(packed, vec![5, 0x0e, 0x71, 0x15, 5, 0x0e, 0x71, 0x16]);
+/// assert_eq!(geohash::unpack_coverage(&packed).unwrap(), vec!["e7115", "e7116"]);
+/// ```
+pub fn pack_coverage(hashes: &[&str]) -> Result<Vec<u8>, Error> {
+    let mut out = vec![];
+    for (index, &hash) in hashes.iter().enumerate() {
+        let bits = to_bits(hash).map_err(|error| GeohashError::InvalidAtIndex { index, error })?;
+        let len = hash.len();
+        out.push(len as u8);
+        out.extend_from_slice(&bits.to_be_bytes()[8 - len.div_ceil(2)..]);
+    }
+    Ok(out)
+}
+
+/// Unpack the geohashes packed by `pack_coverage`, in their original order.
+///
+/// A record running past the end of `bytes` yields
+/// `GeohashError::TruncatedRecord`, and one with a length above 16 or stray
+/// bits set ahead of its hash yields `GeohashError::InvalidRecord`.
+///
+/// ### Examples
+///
+/// ```rust
+/// let hashes = geohash::unpack_coverage(&[2, 0xe7, 1, 0x0e]).expect("Invalid record");
+///
+/// assert_eq!(hashes, vec!["e7", "e"]);
+/// ```
+pub fn unpack_coverage(bytes: &[u8]) -> Result<Vec<String>, Error> {
+    let mut out = vec![];
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let len = bytes[offset] as usize;
+        if len > 16 {
+            bail!(GeohashError::InvalidRecord { offset });
+        }
+        let record = &bytes[offset + 1..];
+        let size = len.div_ceil(2);
+        if record.len() < size {
+            bail!(GeohashError::TruncatedRecord {
+                len: record.len(),
+                expected: size,
+            });
+        }
+        let bits = record[..size]
+            .iter()
+            .fold(0u64, |bits, &byte| (bits << 8) | u64::from(byte));
+        if len < 16 && bits >> (4 * len) != 0 {
+            bail!(GeohashError::InvalidRecord { offset });
+        }
+        out.push(
+            (0..len)
+                .rev()
+                .map(|i| BASE32_CODES[((bits >> (4 * i)) & 0xf) as usize])
+                .collect(),
+        );
+        offset += 1 + size;
+    }
+    Ok(out)
+}
@@ -0,0 +1,154 @@
+use crate::core::{decode, decode_bbox, encode};
+use crate::Coordinate;
+
+use failure::Error;
+
+/// Mean Earth radius in meters.
+pub(crate) const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Pick the geohash length whose cells best match a Web Mercator (slippy
+/// map) tile at zoom level `zoom`.
+///
+/// A tile at zoom `z` spans `360 / 2^z` degrees of longitude, while a cell of
+/// length `n` spans `360 / 4^n` degrees of longitude and half as many of
+/// latitude. Comparing areas at the equator, the closest length is `z / 2`,
+/// raised to `1` at the lowest zooms since `encode` rejects length `0`:
+///
+/// | zoom   | length | cell size (lon × lat)   |
+/// |--------|--------|-------------------------|
+/// | 0–3    | 1      | 90° × 45°               |
+/// | 4–5    | 2      | 22.5° × 11.25°          |
+/// | 6–7    | 3      | 5.625° × 2.8125°        |
+/// | 8–9    | 4      | 1.40625° × 0.703125°    |
+/// | 10–11  | 5      | 0.3515625° × 0.17578125°|
+/// | 12–13  | 6      | ≈ 0.088° × 0.044°       |
+/// | ...    | ...    | ...                     |
+///
+/// Tiles shrink in latitude away from the equator, so at high latitudes the
+/// chosen cells are taller than the tiles they stand in for.
+///
+/// ### Examples
+///
+/// ```rust
+/// assert_eq!(geohash::length_for_zoom(0), 1);
+/// assert_eq!(geohash::length_for_zoom(12), 6);
+/// assert_eq!(geohash::length_for_zoom(13), 6);
+/// ```
+pub fn length_for_zoom(zoom: u32) -> usize {
+    ((zoom / 2) as usize).max(1)
+}
+
+/// Measure a geohash cell as `(width, height)` in meters.
+///
+/// The width is measured along the parallel through the cell's center, so it
+/// shrinks towards the poles while the height stays constant for a given
+/// length. Both use a spherical Earth of mean radius.
+///
+/// ### Examples
+///
+/// ```rust
+/// let (width, height) = geohash::cell_dimensions("e71150").expect("Invalid hash string");
+///
+/// assert_eq!(width.round(), 7721f64);
+/// assert_eq!(height.round(), 4887f64);
+/// ```
+pub fn cell_dimensions(hash_str: &str) -> Result<(f64, f64), Error> {
+    let rect = decode_bbox(hash_str)?;
+    let lat = ((rect.min.y + rect.max.y) / 2f64).to_radians();
+    let width = EARTH_RADIUS * (rect.max.x - rect.min.x).to_radians() * lat.cos();
+    let height = EARTH_RADIUS * (rect.max.y - rect.min.y).to_radians();
+    Ok((width, height))
+}
+
+/// Measure how far encoding `c` at length `len` moves it, as the
+/// `(east-west, north-south)` distances in meters between `c` and the center
+/// of its cell.
+///
+/// Unlike the cell size, which bounds the error for any point, this is the
+/// error for this particular point. The east-west distance is measured along
+/// the parallel through `c`.
+///
+/// ### Examples
+///
+/// ```rust
+/// let coord = geohash::Coordinate { x: 45.0, y: 22.5 };
+///
+/// // the exact center of cell "c"
+/// assert_eq!(geohash::quantization_error(coord, 1).unwrap(), (0f64, 0f64));
+/// ```
+pub fn quantization_error(c: Coordinate<f64>, len: usize) -> Result<(f64, f64), Error> {
+    let (center, _, _) = decode(&encode(c, len)?)?;
+    let lon = EARTH_RADIUS * (center.x - c.x).abs().to_radians() * c.y.to_radians().cos();
+    let lat = EARTH_RADIUS * (center.y - c.y).abs().to_radians();
+    Ok((lon, lat))
+}
+
+/// Compute the initial bearing from the center of cell `a` to the center of
+/// cell `b`, in degrees clockwise from north within `[0, 360)`.
+///
+/// This is the forward azimuth of the great circle between the two centers,
+/// so a path across the antimeridian points the short way round. The bearing
+/// from a cell to itself is `0`.
+///
+/// ### Examples
+///
+/// ```rust
+/// // "6" lies due north of "3"
+/// let north = geohash::bearing("3", "6").expect("Invalid hash string");
+/// let south = geohash::bearing("6", "3").expect("Invalid hash string");
+///
+/// assert_eq!((north, south), (0.0, 180.0));
+/// assert_eq!(geohash::bearing("3", "3").unwrap(), 0.0);
+/// ```
+pub fn bearing(a: &str, b: &str) -> Result<f64, Error> {
+    let (from, _, _) = decode(a)?;
+    let (to, _, _) = decode(b)?;
+    let (lat_a, lat_b) = (from.y.to_radians(), to.y.to_radians());
+    let dlon = (to.x - from.x).to_radians();
+    let y = dlon.sin() * lat_b.cos();
+    let x = lat_a.cos() * lat_b.sin() - lat_a.sin() * lat_b.cos() * dlon.cos();
+    Ok(y.atan2(x).to_degrees().rem_euclid(360f64))
+}
+
+/// Summarize a geohash on one line, for debugging. Requires the `std`
+/// feature, enabled by default.
+///
+/// ### Examples
+///
+/// ```rust
+/// let summary = geohash::describe("e71150").expect("Invalid hash string");
+///
+/// assert_eq!(summary, "e71150 -> center (37.8149, 112.5439), ~7.7km x 4.9km cell");
+/// ```
+#[cfg(feature = "std")]
+pub fn describe(hash_str: &str) -> Result<String, Error> {
+    let (c, _, _) = decode(hash_str)?;
+    let (width, height) = cell_dimensions(hash_str)?;
+    Ok(format!(
+        "{} -> center ({:.4}, {:.4}), ~{} x {} cell",
+        hash_str,
+        c.y,
+        c.x,
+        format_distance(width),
+        format_distance(height)
+    ))
+}
+
+#[cfg(feature = "std")]
+fn format_distance(meters: f64) -> String {
+    if meters >= 1000f64 {
+        format!("{:.1}km", meters / 1000f64)
+    } else {
+        format!("{:.1}m", meters)
+    }
+}
+
+/// Great-circle distance in meters between two coordinates, on a spherical
+/// Earth of mean radius.
+pub(crate) fn haversine(a: Coordinate<f64>, b: Coordinate<f64>) -> f64 {
+    let (lat_a, lat_b) = (a.y.to_radians(), b.y.to_radians());
+    let dlat = (b.y - a.y).to_radians();
+    let dlon = (b.x - a.x).to_radians();
+    let h = (dlat / 2f64).sin().powi(2) + lat_a.cos() * lat_b.cos() * (dlon / 2f64).sin().powi(2);
+    2f64 * EARTH_RADIUS * h.sqrt().min(1f64).asin()
+}
@@ -0,0 +1,79 @@
+use crate::core::{decode, decode_bbox};
+
+use failure::Error;
+
+/// A geohash cell ready to send over the wire as a protobuf message, see
+/// `to_proto`. Requires the `prost` feature.
+///
+/// Field numbers are part of the wire format and never change. The matching
+/// `.proto` definition is:
+///
+/// ```text
+/// message GeohashCell {
+///   string geohash = 1;
+///   double lat = 2;
+///   double lon = 3;
+///   double min_lat = 4;
+///   double min_lon = 5;
+///   double max_lat = 6;
+///   double max_lon = 7;
+/// }
+/// ```
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GeohashCell {
+    /// The geohash, in lowercase.
+    #[prost(string, tag = "1")]
+    pub geohash: String,
+    /// Latitude of the cell's center.
+    #[prost(double, tag = "2")]
+    pub lat: f64,
+    /// Longitude of the cell's center.
+    #[prost(double, tag = "3")]
+    pub lon: f64,
+    /// Southern edge of the cell.
+    #[prost(double, tag = "4")]
+    pub min_lat: f64,
+    /// Western edge of the cell.
+    #[prost(double, tag = "5")]
+    pub min_lon: f64,
+    /// Northern edge of the cell.
+    #[prost(double, tag = "6")]
+    pub max_lat: f64,
+    /// Eastern edge of the cell.
+    #[prost(double, tag = "7")]
+    pub max_lon: f64,
+}
+
+/// Package a geohash's center and bounding box, as `decode` and
+/// `decode_bbox` give them, into a `GeohashCell` message. Requires the
+/// `prost` feature.
+///
+/// ### Examples
+///
+/// ```rust
+/// # #[cfg(feature = "prost")]
+/// # {
+/// use prost::Message;
+///
+/// let cell = geohash::to_proto("c").expect("Invalid hash string");
+///
+/// assert_eq!((cell.lat, cell.lon), (22.5, 45.0));
+/// assert_eq!((cell.min_lon, cell.max_lon), (0.0, 90.0));
+///
+/// let bytes = cell.encode_to_vec();
+/// assert_eq!(geohash::GeohashCell::decode(&bytes[..]).unwrap(), cell);
+/// # }
+/// ```
+pub fn to_proto(hash_str: &str) -> Result<GeohashCell, Error> {
+    let (center, _, _) = decode(hash_str)?;
+    let rect = decode_bbox(hash_str)?;
+    Ok(GeohashCell {
+        geohash: hash_str.to_lowercase(),
+        lat: center.y,
+        lon: center.x,
+        min_lat: rect.min.y,
+        min_lon: rect.min.x,
+        max_lat: rect.max.y,
+        max_lon: rect.max.x,
+    })
+}
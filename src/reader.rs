@@ -0,0 +1,63 @@
+use std::io::{ErrorKind, Read};
+
+use crate::core::{center_and_error, decode_bbox_bytes};
+use crate::{Coordinate, GeohashError};
+
+use failure::Error;
+
+/// Decode a stream of concatenated, fixed-length geohashes, `len` bytes each.
+///
+/// Records are read one at a time, so the whole input never needs to be in
+/// memory. Each item is what `decode` returns for that record. A trailing
+/// record shorter than `len` yields `GeohashError::TruncatedRecord`, and a
+/// read error is passed through; either ends the iteration. A `len` of zero
+/// yields nothing.
+///
+/// ### Examples
+///
+/// ```rust
+/// let input: &[u8] = b"4d8c0e7115";
+///
+/// let decoded: Vec<_> = geohash::decode_reader(input, 5)
+///     .collect::<Result<_, _>>()
+///     .expect("Invalid input");
+///
+/// assert_eq!(decoded, vec![geohash::decode("4d8c0").unwrap(), geohash::decode("e7115").unwrap()]);
+/// ```
+pub fn decode_reader<R: Read>(
+    mut reader: R,
+    len: usize,
+) -> impl Iterator<Item = Result<(Coordinate<f64>, f64, f64), Error>> {
+    let mut buf = vec![0u8; len];
+    let mut done = len == 0;
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let mut filled = 0;
+        while filled < buf.len() {
+            match reader.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => {
+                    done = true;
+                    return Some(Err(e.into()));
+                }
+            }
+        }
+        if filled == 0 {
+            done = true;
+            None
+        } else if filled < buf.len() {
+            done = true;
+            Some(Err(GeohashError::TruncatedRecord {
+                len: filled,
+                expected: buf.len(),
+            }
+            .into()))
+        } else {
+            Some(decode_bbox_bytes(&buf).map(center_and_error))
+        }
+    })
+}
@@ -0,0 +1,53 @@
+use crate::core::decode_bbox;
+use crate::{Coordinate, Direction, Rect};
+
+use failure::Error;
+
+/// Split a geohash cell into its four quadrants, labeled `NW`, `NE`, `SW`
+/// and `SE` in that order.
+///
+/// A character refines a cell twice, each time halving both its width and
+/// its height. The quadrants are the cells after the first of these steps,
+/// which the hex alphabet has no hash for. Each one holds four of the cell's
+/// 16 children.
+///
+/// ### Examples
+///
+/// ```rust
+/// use geohash::Direction;
+///
+/// let quadrants = geohash::quadrants("").expect("Invalid hash string");
+///
+/// assert_eq!(quadrants[0].0, Direction::NW);
+/// assert_eq!(quadrants[0].1.min, geohash::Coordinate { x: -180.0, y: 0.0 });
+/// assert_eq!(quadrants[0].1.max, geohash::Coordinate { x: 0.0, y: 90.0 });
+/// ```
+pub fn quadrants(hash_str: &str) -> Result<[(Direction, Rect<f64>); 4], Error> {
+    let rect = decode_bbox(hash_str)?;
+    let mid = Coordinate {
+        x: (rect.min.x + rect.max.x) / 2f64,
+        y: (rect.min.y + rect.max.y) / 2f64,
+    };
+    let quadrant = |min_x: f64, min_y: f64, max_x: f64, max_y: f64| Rect {
+        min: Coordinate { x: min_x, y: min_y },
+        max: Coordinate { x: max_x, y: max_y },
+    };
+    Ok([
+        (
+            Direction::NW,
+            quadrant(rect.min.x, mid.y, mid.x, rect.max.y),
+        ),
+        (
+            Direction::NE,
+            quadrant(mid.x, mid.y, rect.max.x, rect.max.y),
+        ),
+        (
+            Direction::SW,
+            quadrant(rect.min.x, rect.min.y, mid.x, mid.y),
+        ),
+        (
+            Direction::SE,
+            quadrant(mid.x, rect.min.y, rect.max.x, mid.y),
+        ),
+    ])
+}
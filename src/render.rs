@@ -0,0 +1,65 @@
+use std::collections::HashSet;
+
+use crate::coverage::grid;
+use crate::Rect;
+
+use failure::Error;
+
+/// Draw the length `len` cells covering `rect` as text, one line per row of
+/// cells, marking the cells in `marked` with `#` and the others with `.`.
+///
+/// The cells are those of `cover_bbox`, with north at the top and west on the
+/// left, and lines are separated by `\n`. Meant for eyeballing the output of
+/// the coverage functions while debugging.
+///
+/// ### Examples
+///
+/// ```rust
+/// use std::collections::HashSet;
+///
+/// let rect = geohash::Rect {
+///     min: geohash::Coordinate { x: -10.0, y: -10.0 },
+///     max: geohash::Coordinate { x: 10.0, y: 10.0 },
+/// };
+/// let marked: HashSet<String> = vec!["3".to_string(), "c".to_string()].into_iter().collect();
+///
+/// let map = geohash::ascii_map(&rect, 1, &marked).expect("Invalid rect");
+///
+/// assert_eq!(map, ".#\n#.");
+/// ```
+pub fn ascii_map(rect: &Rect<f64>, len: usize, marked: &HashSet<String>) -> Result<String, Error> {
+    let mut rows: Vec<String> = vec![];
+    for (row, _, hash) in grid(len, rect)? {
+        if row as usize == rows.len() {
+            rows.push(String::new());
+        }
+        rows[row as usize].push(if marked.contains(&hash) { '#' } else { '.' });
+    }
+    rows.reverse();
+    Ok(rows.join("\n"))
+}
+
+/// Pick a stable RGB color for a geohash, for telling cells apart when
+/// drawing them on a map.
+///
+/// The color is derived from a hash of the characters, so it never changes
+/// between runs, while neighboring cells, whose hashes often differ in a
+/// single character, get unrelated colors. Case is ignored.
+///
+/// ### Examples
+///
+/// ```rust
+/// assert_eq!(geohash::color_for("e71150"), geohash::color_for("E71150"));
+/// assert_ne!(geohash::color_for("e71150"), geohash::color_for("e71151"));
+/// ```
+pub fn color_for(hash_str: &str) -> [u8; 3] {
+    // FNV-1a, then the SplitMix64 finalizer to spread nearby inputs apart
+    let mut h = 0xcbf2_9ce4_8422_2325u64;
+    for b in hash_str.bytes() {
+        h = (h ^ u64::from(b.to_ascii_lowercase())).wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^= h >> 31;
+    [(h >> 16) as u8, (h >> 8) as u8, h as u8]
+}
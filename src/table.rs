@@ -0,0 +1,70 @@
+use std::sync::OnceLock;
+
+use crate::core::{hash_value_of_char, neighbors_uncached};
+use crate::Neighbors;
+
+/// Longest geohash whose neighbors `neighbors` looks up in a precomputed
+/// table instead of computing them.
+///
+/// The table covers the 4368 hashes of length 1 to 3, takes about 80 KB and
+/// is built the first time `neighbors` is called on such a hash.
+pub const NEIGHBOR_TABLE_MAX_LEN: usize = 3;
+
+/// Neighbors of every hash up to `NEIGHBOR_TABLE_MAX_LEN` characters, as hash
+/// values in `sw, s, se, w, e, nw, n, ne` order, or `None` where computing
+/// them fails. Hashes of length `len` start at index `(16^len - 16) / 15`.
+static TABLE: OnceLock<Vec<Option<[u16; 8]>>> = OnceLock::new();
+
+/// Look up the neighbors of a hash of at most `NEIGHBOR_TABLE_MAX_LEN`
+/// characters. Returns `None` for longer or invalid hashes, and for hashes
+/// whose neighbors fail to compute, so the caller can fall back to
+/// `neighbors_uncached` and report the error.
+pub(crate) fn cached_neighbors(hash_str: &str) -> Option<Neighbors> {
+    let len = hash_str.len();
+    if len == 0 || len > NEIGHBOR_TABLE_MAX_LEN {
+        return None;
+    }
+    let mut value = 0usize;
+    for c in hash_str.chars() {
+        value = (value << 4) | hash_value_of_char(c).ok()?;
+    }
+    let entry = TABLE.get_or_init(build_table)[offset(len) + value]?;
+    let hash = |value: u16| format!("{:0width$x}", value, width = len);
+    Some(Neighbors {
+        sw: hash(entry[0]),
+        s: hash(entry[1]),
+        se: hash(entry[2]),
+        w: hash(entry[3]),
+        e: hash(entry[4]),
+        nw: hash(entry[5]),
+        n: hash(entry[6]),
+        ne: hash(entry[7]),
+    })
+}
+
+fn offset(len: usize) -> usize {
+    ((1 << (4 * len)) - 16) / 15
+}
+
+fn build_table() -> Vec<Option<[u16; 8]>> {
+    let mut table = Vec::with_capacity(offset(NEIGHBOR_TABLE_MAX_LEN + 1));
+    for len in 1..=NEIGHBOR_TABLE_MAX_LEN {
+        for value in 0..1u16 << (4 * len) {
+            let hash = format!("{:0width$x}", value, width = len);
+            table.push(neighbors_uncached(&hash).ok().map(|n| {
+                let value = |hash: String| u16::from_str_radix(&hash, 16).unwrap();
+                [
+                    value(n.sw),
+                    value(n.s),
+                    value(n.se),
+                    value(n.w),
+                    value(n.e),
+                    value(n.nw),
+                    value(n.n),
+                    value(n.ne),
+                ]
+            }));
+        }
+    }
+    table
+}
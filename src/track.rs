@@ -0,0 +1,68 @@
+use crate::bits::shared_prefix_len;
+use crate::core::{encode, hash_value_of_char};
+use crate::{Coordinate, GeohashError};
+
+use failure::Error;
+
+/// Encode a track of coordinates as front-coded geohashes of length `len`.
+///
+/// Each entry holds the number of leading characters the point's geohash
+/// shares with the previous point's, and the remaining suffix. Consecutive
+/// points of a track are close, so most of each hash is shared and the
+/// suffixes are short. The first entry always has a shared length of `0`.
+///
+/// ### Examples
+///
+/// ```rust
+/// let track = [
+///     geohash::Coordinate { x: 112.5584, y: 37.8324 },
+///     geohash::Coordinate { x: 112.5590, y: 37.8330 },
+/// ];
+///
+/// let encoded = geohash::encode_track(&track, 10).expect("Invalid coordinate");
+///
+/// assert_eq!(encoded, vec![(0, "e71150dc99".to_string()), (8, "cb".to_string())]);
+/// ```
+pub fn encode_track(coords: &[Coordinate<f64>], len: usize) -> Result<Vec<(usize, String)>, Error> {
+    let mut out = Vec::with_capacity(coords.len());
+    let mut previous = String::new();
+    for &c in coords {
+        let hash = encode(c, len)?;
+        let shared = shared_prefix_len(&previous, &hash);
+        out.push((shared, hash[shared..].to_owned()));
+        previous = hash;
+    }
+    Ok(out)
+}
+
+/// Rebuild the full geohashes of a track front-coded by `encode_track`.
+///
+/// Every suffix is validated as geohash characters. An entry claiming to
+/// share more characters than the previous hash has is rejected with
+/// `GeohashError::InvalidSharedPrefix`.
+///
+/// ### Examples
+///
+/// ```rust
+/// let encoded = vec![(0, "e71150dc99".to_string()), (8, "cb".to_string())];
+///
+/// let hashes = geohash::decode_track(&encoded).expect("Invalid track");
+///
+/// assert_eq!(hashes, vec!["e71150dc99", "e71150dccb"]);
+/// ```
+pub fn decode_track(entries: &[(usize, String)]) -> Result<Vec<String>, Error> {
+    let mut out: Vec<String> = Vec::with_capacity(entries.len());
+    for (shared, suffix) in entries {
+        for c in suffix.chars() {
+            hash_value_of_char(c)?;
+        }
+        let previous = out.last().map_or("", |hash| hash.as_str());
+        if *shared > previous.len() {
+            bail!(GeohashError::InvalidSharedPrefix { len: *shared });
+        }
+        let mut hash = previous[..*shared].to_owned();
+        hash.push_str(suffix);
+        out.push(hash);
+    }
+    Ok(out)
+}
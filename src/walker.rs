@@ -0,0 +1,94 @@
+use crate::bits::{grid_from_hash, hash_from_grid};
+
+use failure::Error;
+
+/// Walk a rectangular block of same-length cells row by row.
+///
+/// The block starts at the south-west cell `start` and spans `cols` cells
+/// east and `rows` cells north of it, including `start`. Rows are listed from
+/// south to north, each from west to east. `start` is decoded once and every
+/// further cell is built from its grid position, so no error builds up as
+/// the walk goes on. Cells that would lie past the north pole or the
+/// antimeridian are left out rather than wrapped.
+///
+/// ### Examples
+///
+/// ```rust
+/// let walker = geohash::GridWalker::new("0", 3, 2).expect("Invalid hash string");
+///
+/// let cells: Vec<String> = walker.collect();
+///
+/// assert_eq!(cells, vec!["0", "2", "8", "1", "3", "9"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GridWalker {
+    col: u64,
+    row: u64,
+    len: usize,
+    cols: u64,
+    rows: u64,
+    next_col: u64,
+    next_row: u64,
+}
+
+impl GridWalker {
+    /// Start a walk of `cols` by `rows` cells from the south-west cell
+    /// `start`. Hashes longer than 16 characters are rejected.
+    pub fn new(start: &str, cols: usize, rows: usize) -> Result<GridWalker, Error> {
+        let (col, row) = grid_from_hash(start)?;
+        Ok(GridWalker::from_grid(
+            col,
+            row,
+            start.len(),
+            cols as u64,
+            rows as u64,
+        ))
+    }
+
+    /// Start a walk from the cell at grid position `col`, `row`, see
+    /// `hash_from_grid`.
+    pub(crate) fn from_grid(col: u64, row: u64, len: usize, cols: u64, rows: u64) -> GridWalker {
+        GridWalker {
+            col,
+            row,
+            len,
+            cols,
+            rows,
+            next_col: 0,
+            next_row: 0,
+        }
+    }
+
+    /// Advance the walk, returning the next cell along with its row and
+    /// column within the block.
+    pub(crate) fn step(&mut self) -> Option<(u64, u64, String)> {
+        let cells_per_axis = 1u64 << (2 * self.len);
+        while self.cols > 0 && self.next_row < self.rows {
+            let (r, c) = (self.next_row, self.next_col);
+            self.next_col += 1;
+            if self.next_col == self.cols {
+                self.next_col = 0;
+                self.next_row += 1;
+            }
+
+            let (col, row) = (self.col + c, self.row + r);
+            if row >= cells_per_axis {
+                self.next_row = self.rows;
+            } else if col >= cells_per_axis {
+                self.next_col = 0;
+                self.next_row = r + 1;
+            } else {
+                return Some((r, c, hash_from_grid(col, row, self.len)));
+            }
+        }
+        None
+    }
+}
+
+impl Iterator for GridWalker {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.step().map(|(_, _, hash)| hash)
+    }
+}
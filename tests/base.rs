@@ -1,7 +1,31 @@
 extern crate geo_types;
 extern crate geohash;
+#[cfg(feature = "rand")]
+extern crate rand;
 
-use geohash::{decode, encode, neighbors, Coordinate};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::f64::consts::PI;
+
+use geohash::{
+    adjacency, adjacency_mask, antipode, ascii_map, bearing, bit_diff, bits_of, bounding_geohash,
+    cell_bounds_exact, cell_circle_overlap, cell_dimensions, center, centroid, color_for,
+    common_prefix_len, connected_components, corner_distances, count_cells, cover_bbox,
+    cover_buffered_path, cover_circle_weighted, cover_polygon, cover_polygon_outline, decode,
+    decode_bbox, decode_bbox_bytes, decode_bbox_with_bounds, decode_bounds, decode_f32,
+    decode_lat_lon, decode_normalized, decode_reader, decode_track, decode_with_axis_order,
+    decode_with_max_len, decode_with_offset, difference, downsample, edge_distances, enclosing_cell,
+    encode, encode_batch_simd, encode_bits, encode_f32, encode_iter, encode_lat_lon, encode_stable,
+    encode_track, encode_unchecked, encode_uppercase, encode_with_axis_order, encode_with_bounds,
+    encode_with_mode, encode_with_offset, from_grid_coords, from_index_path, grid, grid_coords,
+    grid_point_hash, hash_range, hash_ranges, hilbert_index, int_range, interpolate_cells,
+    intersection, is_edge_cell, is_stable, length_for_zoom, neighbor, neighbor_fn, neighbors,
+    neighbors_all, neighbors_at_precision, normalize, pack_coverage, quadrants, quantization_error,
+    ring, snap, snap_all, spiral, split_at, tiles_aligned, to_h3_like_path, to_quadkey,
+    unpack_coverage, validate_batch, weighted_centroid, with_neighbors, AxisOrder, Connectivity,
+    Coordinate, Direction, EncodeMode, Geofence, Geohash, GeohashError, GeohashExt, GeohashIndex,
+    GeohashMap, GeohashStrExt, GridWalker, NEIGHBOR_TABLE_MAX_LEN, Neighbors, Point, Rect, RectExt,
+};
 
 #[test]
 fn test_encode() {
@@ -22,12 +46,239 @@ fn test_encode() {
         y: -100f64,
     };
     assert!(encode(c2, 3usize).is_err());
+
+    let err = encode(c0, 0usize).unwrap_err();
+    assert_eq!(format!("{}", err), "invalid hash length: 0");
+    assert!(encode_uppercase(c0, 0usize).is_err());
+    assert!(encode_stable(c0, 0usize).is_err());
+}
+
+#[test]
+fn test_decode_f32() {
+    for hash in [
+        "0",
+        "c",
+        "e71150dc",
+        "4d8c0f18",
+        "ffffffff",
+        "a0000000",
+        "fffffffff",
+        "7ffffffff",
+        "a00000000",
+    ]
+    .iter()
+    {
+        let (c, dx, dy) = decode(hash).unwrap();
+        let (c32, dx32, dy32) = decode_f32(hash).unwrap();
+        assert_eq!((c32.x, c32.y), (c.x as f32, c.y as f32), "{}", hash);
+        assert_eq!((dx32, dy32), (dx as f32, dy as f32), "{}", hash);
+    }
+
+    // Longer hashes stay close to the f64 result, within f32 rounding
+    let (c, _, _) = decode("e71150dc9947").unwrap();
+    let (c32, _, _) = decode_f32("e71150dc9947").unwrap();
+    compare_within(f64::from(c32.x), c.x, 1e-4f64);
+    compare_within(f64::from(c32.y), c.y, 1e-4f64);
+
+    assert!(decode_f32("e7g").is_err());
+}
+
+#[test]
+fn test_encode_unchecked() {
+    for &(x, y) in [
+        (112.5584f64, 37.8324f64),
+        (-120.6623f64, 35.3003f64),
+        (-180f64, -90f64),
+        (180f64, 90f64),
+        (0f64, 0f64),
+    ]
+    .iter()
+    {
+        let c = Coordinate { x, y };
+        for len in 1..17 {
+            assert_eq!(encode_unchecked(c, len), encode(c, len).unwrap());
+        }
+    }
+
+    // Out of range input is not caught, but still gives a hash of `len`
+    let c = Coordinate {
+        x: 190f64,
+        y: -100f64,
+    };
+    assert!(encode(c, 6).is_err());
+    assert_eq!(encode_unchecked(c, 6).len(), 6);
+    assert_eq!(encode_unchecked(c, 0), "");
+}
+
+#[test]
+fn test_encode_batch_simd() {
+    // Whole-degree points land on cell edges at every length
+    let coords: Vec<Coordinate<f64>> = (0..103)
+        .map(|i| Coordinate {
+            x: -180f64 + f64::from(i) * 3.5f64,
+            y: 90f64 - f64::from(i * 7 % 181),
+        })
+        .chain(vec![
+            Coordinate {
+                x: 112.5584f64,
+                y: 37.8324f64,
+            },
+            Coordinate {
+                x: 180f64,
+                y: -90f64,
+            },
+        ])
+        .collect();
+    for len in 1..17 {
+        let batch = encode_batch_simd(&coords, len).unwrap();
+        assert_eq!(batch.len(), coords.len());
+        for (hash, &c) in batch.iter().zip(&coords) {
+            assert_eq!(hash, &encode(c, len).unwrap(), "{:?} at {}", c, len);
+        }
+    }
+    assert_eq!(encode_batch_simd(&[], 6).unwrap(), Vec::<String>::new());
+
+    let mut invalid = coords.clone();
+    invalid[9].x = 190f64;
+    let err = encode_batch_simd(&invalid, 6).unwrap_err();
+    assert!(format!("{}", err).starts_with("invalid input at index 9: invalid coordinate range"));
+    let err = encode_batch_simd(&coords, 0).unwrap_err();
+    assert_eq!(format!("{}", err), "invalid hash length: 0");
+}
+
+#[test]
+fn test_encode_f32() {
+    let c0 = Coordinate {
+        x: 112.5584f32,
+        y: 37.8324f32,
+    };
+    assert_eq!(encode_f32(c0, 8usize).unwrap(), "e71150dc".to_string());
+
+    let c1 = Coordinate {
+        x: 117f32,
+        y: 32f32,
+    };
+    assert_eq!(encode_f32(c1, 3usize).unwrap(), "e65".to_string());
+
+    let c2 = Coordinate {
+        x: 190f32,
+        y: -100f32,
+    };
+    assert!(encode_f32(c2, 3usize).is_err());
+
+    // Up to 9 characters every cell edge is exact in f32, so points on the
+    // edges near the antimeridian and the poles encode as in f64; at 10 they
+    // no longer all do
+    let agrees = |len: usize| {
+        ["f", "7", "8", "0"].iter().all(|first| {
+            let hash = format!("{}{}", first, "f".repeat(len - 1));
+            let rect = decode_bbox(&hash).unwrap();
+            [rect.min, rect.max].iter().all(|corner| {
+                let c32 = Coordinate {
+                    x: corner.x as f32,
+                    y: corner.y as f32,
+                };
+                let c64 = Coordinate {
+                    x: f64::from(c32.x),
+                    y: f64::from(c32.y),
+                };
+                encode_f32(c32, len).unwrap() == encode(c64, len).unwrap()
+            })
+        })
+    };
+    assert!((1..10).all(agrees));
+    assert!(!agrees(10));
+}
+
+#[test]
+fn test_encode_stable() {
+    let origin = Coordinate { x: 0f64, y: 0f64 };
+    assert_eq!(encode(origin, 1usize).unwrap(), "3");
+    assert_eq!(encode_stable(origin, 1usize).unwrap(), "c");
+
+    let corner = Coordinate { x: 90f64, y: 45f64 };
+    assert_eq!(encode(corner, 2usize).unwrap(), "cf");
+    assert_eq!(encode_stable(corner, 2usize).unwrap(), "f0");
+
+    // a point on a cell corner lands in the cell whose min corner it is
+    let c = Coordinate {
+        x: 112.5f64,
+        y: 37.79296875f64,
+    };
+    for len in 1..12usize {
+        let rect = decode_bbox(&encode_stable(c, len).unwrap()).unwrap();
+        assert!(rect.min.x <= c.x && c.x < rect.max.x);
+        assert!(rect.min.y <= c.y && c.y < rect.max.y);
+    }
+    assert_eq!(
+        decode_bbox(&encode_stable(c, 6usize).unwrap()).unwrap().min,
+        c
+    );
+
+    // the outer edges are unaffected
+    let edge = Coordinate {
+        x: 180f64,
+        y: -90f64,
+    };
+    assert_eq!(
+        encode_stable(edge, 3usize).unwrap(),
+        encode(edge, 3usize).unwrap()
+    );
+
+    let c1 = Coordinate {
+        x: 190f64,
+        y: -100f64,
+    };
+    assert!(encode_stable(c1, 3usize).is_err());
+}
+
+#[test]
+fn test_encode_uppercase() {
+    let c0 = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    let upper = encode_uppercase(c0, 12usize).unwrap();
+    assert_eq!(upper, "E71150DC9947".to_string());
+    assert_eq!(decode(&upper).unwrap(), decode("e71150dc9947").unwrap());
+
+    let c1 = Coordinate {
+        x: 190f64,
+        y: -100f64,
+    };
+    assert!(encode_uppercase(c1, 3usize).is_err());
+}
+
+#[cfg(feature = "arrayvec")]
+#[test]
+fn test_encode_small() {
+    use geohash::{encode_small, SMALL_HASH_CAPACITY};
+
+    let c0 = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    assert_eq!(encode_small(c0, 12usize).unwrap().as_str(), "e71150dc9947");
+    assert_eq!(
+        encode_small(c0, SMALL_HASH_CAPACITY).unwrap().as_str(),
+        encode(c0, SMALL_HASH_CAPACITY).unwrap()
+    );
+    assert!(encode_small(c0, SMALL_HASH_CAPACITY + 1).is_err());
+
+    let c1 = Coordinate {
+        x: 190f64,
+        y: -100f64,
+    };
+    assert!(encode_small(c1, 3usize).is_err());
 }
 
 fn compare_within(a: f64, b: f64, diff: f64) {
     assert!(
         (a - b).abs() < diff,
-        format!("{:?} and {:?} should be within {:?}", a, b, diff)
+        "{:?} and {:?} should be within {:?}",
+        a,
+        b,
+        diff
     );
 }
 
@@ -48,6 +299,170 @@ fn test_decode() {
     assert!(decode("wwgj").is_err());
 }
 
+#[test]
+fn test_decode_base32_hint() {
+    let err = decode("e7g").unwrap_err();
+    match err.downcast_ref::<GeohashError>() {
+        Some(GeohashError::Base32HashCharacter { character: 'g' }) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+    let err = decode("e7Z").unwrap_err();
+    match err.downcast_ref::<GeohashError>() {
+        Some(GeohashError::Base32HashCharacter { character: 'Z' }) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    for &hash in ["e7i", "e7!", "e7é"].iter() {
+        let err = decode(hash).unwrap_err();
+        match err.downcast_ref::<GeohashError>() {
+            Some(GeohashError::InvalidHashCharacter { .. }) => {}
+            other => panic!("unexpected error for {}: {:?}", hash, other),
+        }
+    }
+}
+
+#[test]
+fn test_decode_bbox_bytes() {
+    assert_eq!(
+        decode_bbox_bytes(b"e71150").unwrap(),
+        decode_bbox("e71150").unwrap()
+    );
+    assert_eq!(
+        decode_bbox_bytes(b"E71150").unwrap(),
+        decode_bbox("e71150").unwrap()
+    );
+
+    assert!(decode_bbox_bytes(b"wwgj").is_err());
+    assert!(decode_bbox_bytes(&[b'e', 0xc3, 0xa9]).is_err());
+}
+
+#[test]
+fn test_decode_with_max_len() {
+    assert_eq!(
+        decode_with_max_len("e71150", 6).unwrap(),
+        decode("e71150").unwrap()
+    );
+
+    let err = decode_with_max_len("e71150dc99", 6).unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        "hash too long: 10 characters, at most 6 allowed"
+    );
+    // the length is checked before any character
+    let err = decode_with_max_len("wwgjwwgj", 6).unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        "hash too long: 8 characters, at most 6 allowed"
+    );
+    assert!(decode_with_max_len("wwgj", 6).is_err());
+}
+
+#[test]
+fn test_center() {
+    let (coord, _, _) = decode("e71150").unwrap();
+    assert_eq!(center("e71150").unwrap(), (coord.x, coord.y));
+
+    assert!(center("wwgj").is_err());
+}
+
+#[test]
+fn test_decode_bounds() {
+    let rect = decode_bbox("e71150").unwrap();
+    let (min_lat, max_lat, min_lon, max_lon) = decode_bounds("e71150").unwrap();
+    assert_eq!(min_lat, rect.min.y);
+    assert_eq!(max_lat, rect.max.y);
+    assert_eq!(min_lon, rect.min.x);
+    assert_eq!(max_lon, rect.max.x);
+
+    assert!(decode_bounds("wwgj").is_err());
+}
+
+#[test]
+fn test_edge_distances() {
+    let c = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    let rect = decode_bbox(&encode(c, 6usize).unwrap()).unwrap();
+    let (n, e, s, w) = edge_distances(c, 6usize).unwrap();
+    let diff = 1e-9f64;
+    compare_within(n, rect.max.y - c.y, diff);
+    compare_within(e, rect.max.x - c.x, diff);
+    compare_within(s, c.y - rect.min.y, diff);
+    compare_within(w, c.x - rect.min.x, diff);
+    assert!(n >= 0f64 && e >= 0f64 && s >= 0f64 && w >= 0f64);
+    compare_within(n + s, rect.max.y - rect.min.y, diff);
+    compare_within(e + w, rect.max.x - rect.min.x, diff);
+
+    let c1 = Coordinate {
+        x: 190f64,
+        y: -100f64,
+    };
+    assert!(edge_distances(c1, 3usize).is_err());
+}
+
+#[test]
+fn test_corner_distances() {
+    let c = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    let (n, e, s, w) = edge_distances(c, 6usize).unwrap();
+    let corners = corner_distances(c, 6usize).unwrap();
+    // Over a small cell, corners lie about where the edge distances put them
+    // on a flat map, and never closer than the edge along the meridian
+    let meters_per_degree = 6_371_008.8f64.to_radians();
+    let lon_scale = c.y.to_radians().cos();
+    for (&corner, &(dx, dy)) in corners.iter().zip([(e, n), (e, s), (w, s), (w, n)].iter()) {
+        let flat = meters_per_degree * (dx * lon_scale).hypot(dy);
+        compare_within(corner, flat, flat * 1e-3);
+        assert!(corner >= dy * meters_per_degree * 0.999);
+    }
+
+    // A point on the north-east corner belongs to the cell south-west of it
+    let ne_corner = decode_with_offset("e71150", 1f64, 1f64).unwrap();
+    assert_eq!(encode(ne_corner, 6usize).unwrap(), "e71150");
+    let corners = corner_distances(ne_corner, 6usize).unwrap();
+    assert!(corners[0] < 1e-6, "{:?}", corners);
+
+    let c1 = Coordinate {
+        x: 190f64,
+        y: -100f64,
+    };
+    assert!(corner_distances(c1, 3usize).is_err());
+}
+
+#[test]
+fn test_with_bounds() {
+    let earth = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    assert_eq!(
+        encode_with_bounds(earth, 12usize, -180f64, 180f64, -90f64, 90f64).unwrap(),
+        encode(earth, 12usize).unwrap()
+    );
+    assert_eq!(
+        decode_bbox_with_bounds("e71150", -180f64, 180f64, -90f64, 90f64).unwrap(),
+        decode_bbox("e71150").unwrap()
+    );
+
+    // Mars-style 0..360 longitudes
+    let c = Coordinate {
+        x: 292.5584f64,
+        y: 37.8324f64,
+    };
+    let hash = encode_with_bounds(c, 12usize, 0f64, 360f64, -90f64, 90f64).unwrap();
+    assert_eq!(hash, encode(earth, 12usize).unwrap());
+    let rect = decode_bbox_with_bounds(&hash, 0f64, 360f64, -90f64, 90f64).unwrap();
+    assert!(rect.min.x <= c.x && c.x <= rect.max.x);
+    assert!(rect.min.y <= c.y && c.y <= rect.max.y);
+
+    let outside = Coordinate { x: -10f64, y: 0f64 };
+    assert!(encode_with_bounds(outside, 3usize, 0f64, 360f64, -90f64, 90f64).is_err());
+    assert!(decode_bbox_with_bounds("wwgj", 0f64, 360f64, -90f64, 90f64).is_err());
+}
+
 #[test]
 fn test_neighbor() {
     let ns = neighbors( "e71150dc99").unwrap();
@@ -61,6 +476,15 @@ fn test_neighbor() {
     assert_eq!(ns.ne,   "e71150dc9e");
 }
 
+#[test]
+fn test_with_neighbors() {
+    let (cell, ns) = with_neighbors("e71150dc99").unwrap();
+    assert_eq!(cell, "e71150dc99");
+    assert_eq!(ns, neighbors("e71150dc99").unwrap());
+
+    assert!(with_neighbors("wwgj").is_err());
+}
+
 #[test]
 fn test_neighbor_wide() {
     let ns = neighbors("e7115").unwrap();
@@ -73,3 +497,2780 @@ fn test_neighbor_wide() {
     assert_eq!(ns.n, "e7140");
     assert_eq!(ns.ne, "e7142");
 }
+
+fn opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::N => Direction::S,
+        Direction::NE => Direction::SW,
+        Direction::E => Direction::W,
+        Direction::SE => Direction::NW,
+        Direction::S => Direction::N,
+        Direction::SW => Direction::NE,
+        Direction::W => Direction::E,
+        Direction::NW => Direction::SE,
+    }
+}
+
+#[test]
+fn test_neighbor_reverse_across_boundaries() {
+    let directions = [
+        Direction::N,
+        Direction::NE,
+        Direction::E,
+        Direction::SE,
+        Direction::S,
+        Direction::SW,
+        Direction::W,
+        Direction::NW,
+    ];
+    // interior top-level cells, plus cells touching the top-level
+    // boundaries at lon -90/0/90 and lat -45/0/45
+    let hashes = [
+        "3",
+        "6",
+        "9",
+        "c",
+        "c00000",
+        "6aaaaa",
+        "955555",
+        "3fffff",
+        "cfffff",
+        "f00000",
+        "0fffff",
+        "300000",
+        "c2aaaa",
+        "c15555",
+        "e7115",
+        "e71150dc99",
+    ];
+
+    for &hash in hashes.iter() {
+        for &direction in directions.iter() {
+            let n = neighbor(hash, direction).unwrap();
+            assert_eq!(n.len(), hash.len());
+            assert_eq!(
+                neighbor(&n, opposite(direction)).unwrap(),
+                hash,
+                "{:?} neighbor of {} is {}",
+                direction,
+                hash,
+                n
+            );
+        }
+    }
+}
+
+#[test]
+fn test_neighbor_polar_rows() {
+    let south = Coordinate {
+        x: 0.5f64,
+        y: -90f64,
+    };
+    let north = Coordinate {
+        x: 0.5f64,
+        y: 90f64,
+    };
+    let bottom = encode(south, 6usize).unwrap();
+    let top = encode(north, 6usize).unwrap();
+    assert_eq!(bottom, "800022");
+    assert_eq!(top, "d55577");
+
+    for &direction in [Direction::S, Direction::SE, Direction::SW].iter() {
+        assert!(neighbor(&bottom, direction).is_err());
+        assert!(neighbor(&top, opposite(direction)).is_err());
+    }
+    assert_eq!(neighbor(&bottom, Direction::N).unwrap(), "800023");
+    assert_eq!(neighbor(&bottom, Direction::E).unwrap(), "800028");
+    assert_eq!(neighbor(&top, Direction::S).unwrap(), "d55576");
+    assert_eq!(neighbor(&top, Direction::W).unwrap(), "d55575");
+
+    assert!(neighbors(&bottom).is_err());
+    assert!(neighbors(&top).is_err());
+}
+
+#[test]
+fn test_index_path() {
+    let path = to_h3_like_path("e71150dc9947").unwrap();
+    assert_eq!(path, vec![14, 7, 1, 1, 5, 0, 13, 12, 9, 9, 4, 7]);
+    assert_eq!(from_index_path(&path).unwrap(), "e71150dc9947");
+
+    assert!(to_h3_like_path("wwgj").is_err());
+    assert!(from_index_path(&[1, 16]).is_err());
+}
+
+#[test]
+fn test_quadkey() {
+    assert_eq!(to_quadkey("0").unwrap(), "22");
+    assert_eq!(to_quadkey("f").unwrap(), "11");
+    assert_eq!(to_quadkey("e71150").unwrap(), "130120200022");
+    assert_eq!(to_quadkey("").unwrap(), "");
+
+    assert!(to_quadkey("wwgj").is_err());
+}
+
+#[test]
+fn test_length_for_zoom() {
+    assert_eq!(length_for_zoom(0), 1);
+    assert_eq!(length_for_zoom(1), 1);
+    assert_eq!(length_for_zoom(2), 1);
+    assert_eq!(length_for_zoom(3), 1);
+    assert_eq!(length_for_zoom(9), 4);
+    assert_eq!(length_for_zoom(18), 9);
+
+    // every chosen length encodes
+    let c = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    for zoom in 0..24u32 {
+        assert!(encode(c, length_for_zoom(zoom)).is_ok());
+    }
+
+    // past the clamped zooms, the chosen cell is never more than a factor of
+    // two off the tile's area
+    for zoom in 2..24u32 {
+        let tile_width = 360f64 / 2f64.powi(zoom as i32);
+        let (_, lon_err, lat_err) = decode(&"0".repeat(length_for_zoom(zoom))).unwrap();
+        let ratio = (4f64 * lon_err * lat_err) / (tile_width * tile_width);
+        assert!((0.5f64..=2f64).contains(&ratio), "zoom {}: {}", zoom, ratio);
+    }
+}
+
+#[test]
+fn test_bit_diff() {
+    assert_eq!(bit_diff("e71150", "e71150").unwrap(), 0);
+    assert_eq!(bit_diff("e71150", "e71151").unwrap(), 0x1);
+    assert_eq!(bit_diff("e71150", "171150").unwrap(), 0xf0_0000);
+    assert_eq!(
+        bit_diff("ffffffffffffffff", "0000000000000000").unwrap(),
+        u64::MAX
+    );
+
+    assert!(bit_diff("e711", "e7115").is_err());
+    assert!(bit_diff("wwgj", "e711").is_err());
+    assert!(bit_diff("00000000000000000", "00000000000000000").is_err());
+}
+
+#[test]
+fn test_split_at() {
+    assert_eq!(split_at("e71150dc9", 4).unwrap(), ("e711", "50dc9"));
+    assert_eq!(split_at("E7115", 2).unwrap(), ("E7", "115"));
+    assert_eq!(split_at("e7115", 0).unwrap(), ("", "e7115"));
+    assert_eq!(split_at("e7115", 5).unwrap(), ("e7115", ""));
+
+    // The prefix and suffix join back into the hash
+    let (prefix, suffix) = split_at("e71150dc9", 6).unwrap();
+    assert_eq!(format!("{}{}", prefix, suffix), "e71150dc9");
+
+    let err = split_at("e7115", 6).unwrap_err();
+    assert_eq!(format!("{}", err), "invalid hash length: 6");
+    assert!(split_at("e7g15", 2).is_err());
+    assert!(split_at("é", 1).is_err());
+}
+
+#[test]
+fn test_common_prefix_len() {
+    assert_eq!(common_prefix_len("e71150", "e71150").unwrap(), 6);
+    assert_eq!(common_prefix_len("e71150", "e7114f").unwrap(), 4);
+    assert_eq!(common_prefix_len("e71150", "171150").unwrap(), 0);
+    assert_eq!(common_prefix_len("e711", "e71150").unwrap(), 4);
+    assert_eq!(common_prefix_len("E71A", "e71a3").unwrap(), 4);
+    assert_eq!(common_prefix_len("", "e71").unwrap(), 0);
+
+    // Invalid characters are caught even after the hashes diverge
+    assert!(common_prefix_len("e71", "e8z").is_err());
+    assert!(common_prefix_len("wwgj", "e711").is_err());
+}
+
+#[test]
+fn test_encode_bits() {
+    let c = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    let full = u64::from_str_radix(&encode(c, 16).unwrap(), 16).unwrap();
+    for bits in 1..=64 {
+        let key = encode_bits(c, bits).unwrap();
+        assert_eq!(key, full >> (64 - bits), "{} bits", bits);
+        if bits < 64 {
+            assert!(key < 1u64 << bits);
+        }
+    }
+    assert_eq!(encode_bits(c, 48).unwrap(), 0xe711_50dc_9947);
+
+    // The last bit of an odd width is a longitude bit, so points east and
+    // west of the cell's middle differ there
+    let (center, _, _) = decode("e7").unwrap();
+    let east = Coordinate {
+        x: center.x + 0.1,
+        y: center.y,
+    };
+    let west = Coordinate {
+        x: center.x - 0.1,
+        y: center.y,
+    };
+    assert_eq!(
+        encode_bits(east, 9).unwrap(),
+        encode_bits(west, 9).unwrap() + 1
+    );
+
+    assert!(encode_bits(c, 0).is_err());
+    let err = encode_bits(c, 65).unwrap_err();
+    assert_eq!(format!("{}", err), "invalid bit depth: 65");
+    assert!(encode_bits(Coordinate { x: 190f64, y: 0f64 }, 20).is_err());
+}
+
+#[test]
+fn test_difference() {
+    assert_eq!(difference(&["e7", "e8"], &["e8"]), vec!["e7"]);
+    assert_eq!(difference(&["e71", "e72"], &["e7"]), Vec::<String>::new());
+    assert_eq!(difference(&["e7", "e7", "e71"], &[]), vec!["e7", "e71"]);
+
+    let diff = difference(&["e7"], &["e71f"]);
+    assert_eq!(diff.len(), 15 + 15);
+    assert!(diff.contains(&"e70".to_string()));
+    assert!(diff.contains(&"e71e".to_string()));
+    assert!(!diff.contains(&"e71".to_string()));
+    assert!(!diff.iter().any(|cell| cell.starts_with("e71f")));
+}
+
+#[test]
+fn test_intersection() {
+    assert_eq!(intersection(&["e7"], &["e8"]), Vec::<String>::new());
+    assert_eq!(intersection(&["e7", "e8"], &["e8", "e7"]), vec!["e7", "e8"]);
+    assert_eq!(
+        intersection(&["e"], &["e71", "e7", "e8f"]),
+        vec!["e7", "e8f"]
+    );
+    assert_eq!(intersection(&["e71", "e7f"], &["e7"]), vec!["e71", "e7f"]);
+    assert_eq!(
+        intersection(&["e71", "e7f"], &["e"]),
+        intersection(&["e"], &["e71", "e7f"])
+    );
+    assert_eq!(intersection(&[], &["e"]), Vec::<String>::new());
+}
+
+#[test]
+fn test_cover_bbox() {
+    let rect = Rect {
+        min: Coordinate {
+            x: 112.5f64,
+            y: 37.8f64,
+        },
+        max: Coordinate {
+            x: 112.7f64,
+            y: 37.9f64,
+        },
+    };
+    let cells = cover_bbox(&rect, 4usize).unwrap();
+    // 112.5 lies on a column edge, so the column west of it is included
+    assert_eq!(cells.len(), 2);
+    for &(x, y) in [(112.5, 37.8), (112.7, 37.9), (112.6, 37.85), (112.5, 37.9)].iter() {
+        let hash = encode(Coordinate { x, y }, 4usize).unwrap();
+        assert!(cells.contains(&hash), "{} not in {:?}", hash, cells);
+    }
+    for cell in cells.iter() {
+        let bbox = decode_bbox(cell).unwrap();
+        assert!(bbox.min.x <= rect.max.x && bbox.max.x >= rect.min.x);
+        assert!(bbox.min.y <= rect.max.y && bbox.max.y >= rect.min.y);
+    }
+
+    let world = Rect {
+        min: Coordinate {
+            x: -180f64,
+            y: -90f64,
+        },
+        max: Coordinate {
+            x: 180f64,
+            y: 90f64,
+        },
+    };
+    assert_eq!(cover_bbox(&world, 1usize).unwrap().len(), 16);
+    assert!(cover_bbox(&world, 0usize).is_err());
+    assert!(Geofence::from_rect(&world, 0usize).is_err());
+    assert!(hash_ranges(&world, 0usize).is_err());
+    assert!(hash_range(&world, 0usize).is_err());
+    assert!(tiles_aligned(&world, 0usize).is_err());
+    assert_eq!(count_cells(&world, 0usize), usize::MAX);
+
+    let outside = Rect {
+        min: Coordinate { x: 170f64, y: 0f64 },
+        max: Coordinate {
+            x: 190f64,
+            y: 10f64,
+        },
+    };
+    assert!(cover_bbox(&outside, 3usize).is_err());
+    assert!(cover_bbox(&rect, 17usize).is_err());
+}
+
+#[test]
+fn test_tiles_aligned() {
+    let bbox = decode_bbox("9c8e").unwrap();
+    assert_eq!(tiles_aligned(&bbox, 4usize).unwrap(), vec!["9c8e"]);
+    let tiles = tiles_aligned(&bbox, 5usize).unwrap();
+    assert_eq!(tiles.len(), 16);
+    let unique: HashSet<_> = tiles.iter().collect();
+    assert_eq!(unique.len(), 16);
+    assert!(tiles.iter().all(|tile| tile.starts_with("9c8e")));
+
+    // Neighbouring rects share an edge but no tiles
+    let east = decode_bbox(&neighbor("9c8e", Direction::E).unwrap()).unwrap();
+    let west_tiles: HashSet<_> = tiles.into_iter().collect();
+    for tile in tiles_aligned(&east, 5usize).unwrap() {
+        assert!(!west_tiles.contains(&tile));
+    }
+
+    let point = Rect {
+        min: Coordinate {
+            x: 112.5f64,
+            y: 37.8f64,
+        },
+        max: Coordinate {
+            x: 112.5f64,
+            y: 37.8f64,
+        },
+    };
+    assert_eq!(tiles_aligned(&point, 4usize).unwrap().len(), 1);
+    assert!(tiles_aligned(&bbox, 17usize).is_err());
+}
+
+#[test]
+fn test_geofence() {
+    let rect = Rect {
+        min: Coordinate {
+            x: 112.5f64,
+            y: 37.8f64,
+        },
+        max: Coordinate {
+            x: 112.7f64,
+            y: 37.9f64,
+        },
+    };
+    let fence = Geofence::from_rect(&rect, 5usize).unwrap();
+    assert!(fence.contains(Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    }));
+    assert!(fence.contains(rect.min));
+    assert!(fence.contains(rect.max));
+    assert!(!fence.contains(Coordinate {
+        x: 112f64,
+        y: 37.85f64,
+    }));
+    assert!(!fence.contains(Coordinate {
+        x: 190f64,
+        y: -100f64,
+    }));
+
+    let mixed = Geofence::from_cells(vec!["e7".to_string(), "E8F1".to_string()]).unwrap();
+    assert!(mixed.contains(decode("e71150").unwrap().0));
+    assert!(mixed.contains(decode("e8f1").unwrap().0));
+    assert!(!mixed.contains(decode("e8f2").unwrap().0));
+    assert!(mixed.cells().contains("e8f1"));
+
+    assert!(!Geofence::from_cells(Vec::new()).unwrap().contains(rect.min));
+    assert!(Geofence::from_cells(vec!["e7".to_string(), "e7g".to_string()]).is_err());
+
+    // The world cell holds every valid point
+    let world = Geofence::from_cells(vec![String::new()]).unwrap();
+    assert!(world.contains(rect.min));
+    assert!(world.contains(Coordinate {
+        x: -180f64,
+        y: 90f64,
+    }));
+    assert!(!world.contains(Coordinate { x: 190f64, y: 0f64 }));
+
+    let triangle = [
+        Coordinate {
+            x: -10f64,
+            y: -10f64,
+        },
+        Coordinate {
+            x: 10f64,
+            y: -10f64,
+        },
+        Coordinate { x: 10f64, y: 10f64 },
+    ];
+    let fence = Geofence::from_polygon(&triangle, 3usize).unwrap();
+    assert!(fence.contains(Coordinate { x: 5f64, y: -5f64 }));
+    assert!(!fence.contains(Coordinate { x: -9f64, y: 9f64 }));
+    assert!(Geofence::from_polygon(&[], 3usize).is_err());
+}
+
+#[test]
+fn test_count_cells() {
+    let rect = Rect {
+        min: Coordinate {
+            x: 112.3f64,
+            y: 37.8f64,
+        },
+        max: Coordinate {
+            x: 113.7f64,
+            y: 38.9f64,
+        },
+    };
+    for len in 1..6usize {
+        assert_eq!(
+            count_cells(&rect, len),
+            cover_bbox(&rect, len).unwrap().len()
+        );
+    }
+
+    let world = Rect {
+        min: Coordinate {
+            x: -180f64,
+            y: -90f64,
+        },
+        max: Coordinate {
+            x: 180f64,
+            y: 90f64,
+        },
+    };
+    assert_eq!(count_cells(&world, 8usize), 1 << 32);
+    assert_eq!(count_cells(&world, 17usize), usize::MAX);
+
+    let inverted = Rect {
+        min: rect.max,
+        max: rect.min,
+    };
+    assert_eq!(count_cells(&inverted, 3usize), 0);
+}
+
+#[test]
+fn test_buffer() {
+    let rect = decode_bbox("e71150").unwrap();
+    let buffered = rect.buffer(0.5f64, 0.25f64);
+    assert_eq!(buffered.min.x, rect.min.x - 0.5f64);
+    assert_eq!(buffered.min.y, rect.min.y - 0.25f64);
+    assert_eq!(buffered.max.x, rect.max.x + 0.5f64);
+    assert_eq!(buffered.max.y, rect.max.y + 0.25f64);
+
+    let shrunk = buffered.buffer(-0.5f64, -0.25f64);
+    compare_within(shrunk.min.x, rect.min.x, 1e-9f64);
+    compare_within(shrunk.max.y, rect.max.y, 1e-9f64);
+
+    let corner = decode_bbox("0").unwrap().buffer(10f64, 10f64);
+    assert_eq!((corner.min.x, corner.min.y), (-180f64, -90f64));
+    assert_eq!((corner.max.x, corner.max.y), (-80f64, -35f64));
+}
+
+#[test]
+fn test_centroid() {
+    let (c, _, _) = decode("e71150").unwrap();
+    let single = centroid(&["e71150"]).unwrap();
+    compare_within(single.x, c.x, 1e-9f64);
+    compare_within(single.y, c.y, 1e-9f64);
+
+    // symmetric around the equator and the prime meridian
+    let origin = centroid(&["3", "6", "9", "c"]).unwrap();
+    compare_within(origin.x, 0f64, 1e-9f64);
+    compare_within(origin.y, 0f64, 1e-9f64);
+
+    // across the antimeridian
+    let west = decode("400000").unwrap().0;
+    let east = decode("eaaaaa").unwrap().0;
+    let date_line = centroid(&["400000", "eaaaaa"]).unwrap();
+    compare_within(date_line.x.abs(), 180f64, 1e-3f64);
+    compare_within(date_line.y, (west.y + east.y) / 2f64, 1e-3f64);
+
+    assert!(centroid(&[]).is_err());
+    assert!(centroid(&["e71150", "wwgj"]).is_err());
+}
+
+#[test]
+fn test_weighted_centroid() {
+    // Equal weights match the plain centroid
+    let hashes = ["e71150", "e71151", "e7115f", "e65b4a"];
+    let equal: Vec<(&str, f64)> = hashes.iter().map(|&hash| (hash, 2.5f64)).collect();
+    assert_eq!(
+        weighted_centroid(&equal, 10).unwrap(),
+        encode(centroid(&hashes).unwrap(), 10).unwrap()
+    );
+
+    // A single weighted cell wins, and zero weights count for nothing
+    assert_eq!(
+        weighted_centroid(&[("e71150", 1f64), ("e65b4a", 0f64)], 6).unwrap(),
+        "e71150"
+    );
+
+    // The mean is pulled towards the heavier cell
+    let (a, _, _) = decode("e71150").unwrap();
+    let (b, _, _) = decode("e7115f").unwrap();
+    let hash = weighted_centroid(&[("e71150", 3f64), ("e7115f", 1f64)], 12).unwrap();
+    let (mean, _, _) = decode(&hash).unwrap();
+    compare_within(mean.x, a.x + (b.x - a.x) / 4f64, 1e-4f64);
+    compare_within(mean.y, a.y + (b.y - a.y) / 4f64, 1e-4f64);
+
+    // Across the antimeridian
+    let hash = weighted_centroid(&[("400000", 1f64), ("eaaaaa", 1f64)], 4).unwrap();
+    assert!(hash.starts_with('4') || hash.starts_with('e'), "{}", hash);
+
+    assert!(weighted_centroid(&[], 6).is_err());
+    let err = weighted_centroid(&[("e71150", 0f64), ("e71151", 0f64)], 6).unwrap_err();
+    assert_eq!(format!("{}", err), "total weight is zero");
+    let err = weighted_centroid(&[("e71150", 1f64), ("e71151", -1f64)], 6).unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        "invalid weight: -1, expected a finite non-negative value"
+    );
+    assert!(weighted_centroid(&[("e71150", f64::NAN)], 6).is_err());
+    assert!(weighted_centroid(&[("wwgj", 1f64)], 6).is_err());
+    assert!(weighted_centroid(&[("e71150", 1f64)], 0).is_err());
+}
+
+#[test]
+fn test_enclosing_cell() {
+    assert_eq!(enclosing_cell("e71150", "e71150").unwrap(), "e71150");
+    assert_eq!(enclosing_cell("e71150", "e7115").unwrap(), "e7115");
+    assert_eq!(enclosing_cell("E71150", "e7114f").unwrap(), "e711");
+    assert_eq!(enclosing_cell("3", "c").unwrap(), "");
+
+    // Both cells lie entirely inside the result
+    for &(a, b) in [
+        ("e71150dc", "e7115f"),
+        ("4d8c0f18", "4d8c0e"),
+        ("e7", "e71150"),
+    ]
+    .iter()
+    {
+        let outer = decode_bbox(&enclosing_cell(a, b).unwrap()).unwrap();
+        for hash in [a, b].iter() {
+            let inner = decode_bbox(hash).unwrap();
+            assert!(outer.min.x <= inner.min.x && inner.max.x <= outer.max.x);
+            assert!(outer.min.y <= inner.min.y && inner.max.y <= outer.max.y);
+        }
+    }
+
+    // The corners of "e7" and "e8" give a coarser cell, since the south-west
+    // corner of "e7" encodes into the cell below it
+    let corners: Vec<Coordinate<f64>> = ["e7", "e8"]
+        .iter()
+        .flat_map(|hash| {
+            let rect = decode_bbox(hash).unwrap();
+            vec![rect.min, rect.max]
+        })
+        .collect();
+    assert_eq!(enclosing_cell("e7", "e8").unwrap(), "e");
+    assert_eq!(bounding_geohash(&corners).unwrap(), "");
+
+    assert!(enclosing_cell("e71150", "e7g").is_err());
+    assert!(enclosing_cell("wwgj", "e711").is_err());
+}
+
+#[test]
+fn test_bounding_geohash() {
+    let c0 = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    assert_eq!(
+        bounding_geohash(&[c0]).unwrap(),
+        encode(c0, 16usize).unwrap()
+    );
+
+    let (c1, _, _) = decode("e71150dc").unwrap();
+    let (c2, _, _) = decode("e71150d0").unwrap();
+    let (c3, _, _) = decode("e7115f").unwrap();
+    assert_eq!(bounding_geohash(&[c1, c2]).unwrap(), "e71150d");
+    assert_eq!(bounding_geohash(&[c1, c2, c3]).unwrap(), "e7115");
+
+    // either side of the equator
+    let north = Coordinate {
+        x: 10f64,
+        y: 0.1f64,
+    };
+    let south = Coordinate {
+        x: 10f64,
+        y: -0.1f64,
+    };
+    assert_eq!(bounding_geohash(&[north, south]).unwrap(), "");
+
+    assert!(bounding_geohash(&[]).is_err());
+    assert!(bounding_geohash(&[c0, Coordinate { x: 190f64, y: 0f64 }]).is_err());
+}
+
+#[test]
+fn test_grid() {
+    let rect = Rect {
+        min: Coordinate {
+            x: 112.3f64,
+            y: 37.8f64,
+        },
+        max: Coordinate {
+            x: 113.7f64,
+            y: 38.9f64,
+        },
+    };
+    let cells: Vec<(u32, u32, String)> = grid(4usize, &rect).unwrap().collect();
+    let hashes: Vec<String> = cells.iter().map(|(_, _, hash)| hash.clone()).collect();
+    assert_eq!(hashes, cover_bbox(&rect, 4usize).unwrap());
+
+    let rows = cells.iter().map(|&(row, _, _)| row).max().unwrap() + 1;
+    let cols = cells.iter().map(|&(_, col, _)| col).max().unwrap() + 1;
+    assert_eq!((rows * cols) as usize, cells.len());
+    for (row, col, hash) in cells.iter() {
+        if *col > 0 {
+            let west = &cells[(row * cols + col - 1) as usize].2;
+            assert_eq!(&neighbor(hash, Direction::W).unwrap(), west);
+        }
+        if *row > 0 {
+            let south = &cells[((row - 1) * cols + col) as usize].2;
+            assert_eq!(&neighbor(hash, Direction::S).unwrap(), south);
+        }
+    }
+
+    assert!(grid(17usize, &rect).is_err());
+}
+
+#[test]
+fn test_bits_of() {
+    assert_eq!(bits_of(""), 0);
+    assert_eq!(bits_of("e71150dc9947"), 48);
+
+    // a length 5 hex hash has the resolution of a length 4 base32 geohash:
+    // 10 longitude bits and 10 latitude bits
+    let (_, lon_err, lat_err) = decode("e7115").unwrap();
+    assert_eq!(bits_of("e7115"), 4 * 5);
+    assert_eq!(2f64 * lon_err, 360f64 / 2f64.powi(10));
+    assert_eq!(2f64 * lat_err, 180f64 / 2f64.powi(10));
+}
+
+#[test]
+fn test_ring() {
+    assert_eq!(ring("e71150dc99", 0).unwrap(), vec!["e71150dc99"]);
+
+    let ns = neighbors("e71150dc99").unwrap();
+    let mut ring1 = ring("e71150dc99", 1).unwrap();
+    let mut expected = vec![ns.sw, ns.s, ns.se, ns.w, ns.e, ns.nw, ns.n, ns.ne];
+    ring1.sort();
+    expected.sort();
+    assert_eq!(ring1, expected);
+
+    let ring2 = ring("e71150dc99", 2).unwrap();
+    assert_eq!(ring2.len(), 16);
+    assert!(!ring2.iter().any(|cell| ring1.contains(cell)));
+
+    // Rings are listed row by row from the south-west corner
+    let ring3 = ring("e71150dc99", 3).unwrap();
+    assert_eq!(ring3.len(), 24);
+    let centers: Vec<(f64, f64)> = ring3
+        .iter()
+        .map(|cell| {
+            let (c, _, _) = decode(cell).unwrap();
+            (c.y, c.x)
+        })
+        .collect();
+    assert!(centers.windows(2).all(|pair| pair[0] < pair[1]));
+
+    // Only the border is walked, so wide rings stay cheap
+    assert_eq!(ring("e71150dc99", 100_000).unwrap().len(), 800_000);
+
+    // bottom-left corner of the world: only the cells north and east remain
+    assert_eq!(ring("0", 1).unwrap(), vec!["2", "1", "3"]);
+    assert!(ring("wwgj", 1).is_err());
+}
+
+#[test]
+fn test_spiral() {
+    let cells: Vec<String> = spiral("e71150dc99").unwrap().take(25).collect();
+    assert_eq!(cells[0], "e71150dc99");
+    assert_eq!(&cells[1..9], &ring("e71150dc99", 1).unwrap()[..]);
+    assert_eq!(&cells[9..], &ring("e71150dc99", 2).unwrap()[..]);
+
+    // the whole world at length 1, each cell once
+    let mut world: Vec<String> = spiral("0").unwrap().collect();
+    assert_eq!(world.len(), 16);
+    world.sort();
+    world.dedup();
+    assert_eq!(world.len(), 16);
+
+    assert!(spiral("wwgj").is_err());
+}
+
+#[test]
+fn test_normalize() {
+    let c = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    assert_eq!(normalize(c), c);
+
+    let wrapped = |x: f64| normalize(Coordinate { x, y: 0f64 }).x;
+    assert_eq!(wrapped(180f64), -180f64);
+    assert_eq!(wrapped(-180f64), -180f64);
+    assert_eq!(wrapped(190f64), -170f64);
+    assert_eq!(wrapped(-190f64), 170f64);
+    assert_eq!(wrapped(540f64), -180f64);
+    assert_eq!(wrapped(-720f64 + 10f64), 10f64);
+
+    let clamped = |y: f64| normalize(Coordinate { x: 0f64, y }).y;
+    assert_eq!(clamped(90f64), 90f64);
+    assert_eq!(clamped(100f64), 90f64);
+    assert_eq!(clamped(-100f64), -90f64);
+
+    assert!(encode(
+        normalize(Coordinate {
+            x: 190f64,
+            y: -100f64,
+        }),
+        3usize
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_int_range() {
+    assert_eq!(int_range("e71", 12).unwrap(), (0xe71, 0xe71));
+    assert_eq!(
+        int_range("e71", 14).unwrap(),
+        (0xe71 << 2, (0xe71 << 2) | 0x3)
+    );
+    assert_eq!(int_range("", 64).unwrap(), (0, u64::MAX));
+    assert_eq!(
+        int_range("e71150dc9947", 64).unwrap(),
+        (0xe711_50dc_9947_0000, 0xe711_50dc_9947_ffff)
+    );
+
+    // every longer hash under the prefix falls in the range
+    let (min, max) = int_range("e71", 24).unwrap();
+    for &hash in ["e71000", "e71150", "e71fff"].iter() {
+        let (key, _) = int_range(hash, 24).unwrap();
+        assert!(min <= key && key <= max);
+    }
+    let (key, _) = int_range("e72000", 24).unwrap();
+    assert!(key > max);
+
+    assert!(int_range("e71", 8).is_err());
+    assert!(int_range("e71", 65).is_err());
+    assert!(int_range("wwgj", 32).is_err());
+}
+
+#[test]
+fn test_cell_dimensions() {
+    // at the equator a length 1 cell spans 90 by 45 degrees
+    let (width, height) = cell_dimensions("3").unwrap();
+    let quarter = 6_371_008.8f64 * std::f64::consts::FRAC_PI_2;
+    compare_within(height, quarter / 2f64, 1e-6f64);
+    assert!(width < quarter && width > quarter * 0.9f64);
+
+    // same height, narrower towards the pole
+    let (width_north, height_north) = cell_dimensions("f").unwrap();
+    assert!(width_north < width);
+    compare_within(height_north, height, 1e-6f64);
+
+    assert!(cell_dimensions("wwgj").is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_describe() {
+    use geohash::describe;
+
+    assert_eq!(
+        describe("e71150").unwrap(),
+        "e71150 -> center (37.8149, 112.5439), ~7.7km x 4.9km cell"
+    );
+    assert_eq!(
+        describe("e71150dc99").unwrap(),
+        "e71150dc99 -> center (37.8324, 112.5585), ~30.2m x 19.1m cell"
+    );
+    assert!(describe("wwgj").is_err());
+}
+
+#[test]
+fn test_grid_point_hash() {
+    assert_eq!(grid_point_hash(0, 0, 1).unwrap(), "3");
+    assert_eq!(grid_point_hash(-180, -90, 3).unwrap(), "000");
+    assert_eq!(grid_point_hash(180, 90, 3).unwrap(), "fff");
+    let c = Coordinate {
+        x: 112f64,
+        y: 37f64,
+    };
+    assert_eq!(grid_point_hash(112, 37, 8).unwrap(), encode(c, 8).unwrap());
+
+    assert!(grid_point_hash(181, 0, 3).is_err());
+    assert!(grid_point_hash(0, -91, 3).is_err());
+}
+
+#[test]
+fn test_decode_reader() {
+    let input: &[u8] = b"e71150e65b4awwgj004d8c0f18";
+    let decoded: Vec<_> = decode_reader(input, 6).collect();
+    assert_eq!(decoded.len(), 5);
+    assert_eq!(decoded[0].as_ref().unwrap(), &decode("e71150").unwrap());
+    assert_eq!(decoded[1].as_ref().unwrap(), &decode("e65b4a").unwrap());
+    assert!(decoded[2].is_err());
+    assert_eq!(decoded[3].as_ref().unwrap(), &decode("4d8c0f").unwrap());
+    let err = decoded[4].as_ref().unwrap_err();
+    match err.downcast_ref::<GeohashError>() {
+        Some(GeohashError::TruncatedRecord {
+            len: 2,
+            expected: 6,
+        }) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    let empty: &[u8] = b"";
+    assert_eq!(decode_reader(empty, 6).count(), 0);
+    assert_eq!(decode_reader(input, 0).count(), 0);
+}
+
+#[test]
+fn test_pack_coverage() {
+    let rect = Rect {
+        min: Coordinate {
+            x: 112.13f64,
+            y: 37.61f64,
+        },
+        max: Coordinate {
+            x: 112.94f64,
+            y: 38.12f64,
+        },
+    };
+    let cells = cover_bbox(&rect, 5).unwrap();
+    let refs: Vec<&str> = cells.iter().map(|hash| hash.as_str()).collect();
+    let packed = pack_coverage(&refs).unwrap();
+    assert_eq!(packed.len(), cells.len() * 4);
+    assert_eq!(unpack_coverage(&packed).unwrap(), cells);
+
+    // Mixed lengths, including the empty hash and the longest allowed
+    let mixed = ["", "0", "f0", "E71150DC9", "ffffffffffffffff"];
+    let packed = pack_coverage(&mixed).unwrap();
+    assert_eq!(
+        unpack_coverage(&packed).unwrap(),
+        vec!["", "0", "f0", "e71150dc9", "ffffffffffffffff"]
+    );
+    assert_eq!(pack_coverage(&[]).unwrap(), Vec::<u8>::new());
+    assert_eq!(unpack_coverage(&[]).unwrap(), Vec::<String>::new());
+
+    let err = pack_coverage(&["e7", "e7g"]).unwrap_err();
+    assert!(format!("{}", err).starts_with("invalid input at index 1: "));
+    match err.downcast_ref::<GeohashError>() {
+        Some(GeohashError::InvalidAtIndex { index: 1, .. }) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(pack_coverage(&["fffffffffffffffff"]).is_err());
+
+    let err = unpack_coverage(&[5, 0x0e, 0x71]).unwrap_err();
+    assert_eq!(format!("{}", err), "truncated record: 2 of 3 bytes");
+    let err = unpack_coverage(&[2, 0xe7, 1, 0xfe]).unwrap_err();
+    assert_eq!(format!("{}", err), "invalid record at byte 2");
+    assert!(unpack_coverage(&[17, 0, 0, 0, 0, 0, 0, 0, 0, 0]).is_err());
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn test_random_point_in() {
+    use geohash::random_point_in;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let rect = decode_bbox("e71150").unwrap();
+    for _ in 0..100 {
+        let c = random_point_in("e71150", &mut rng).unwrap();
+        assert!(rect.min.x <= c.x && c.x <= rect.max.x);
+        assert!(rect.min.y <= c.y && c.y <= rect.max.y);
+    }
+    assert!(random_point_in("wwgj", &mut rng).is_err());
+}
+
+#[test]
+fn test_track() {
+    let track: Vec<Coordinate<f64>> = (0..20)
+        .map(|i| Coordinate {
+            x: 112.5584f64 + 0.0003f64 * f64::from(i),
+            y: 37.8324f64 - 0.0002f64 * f64::from(i),
+        })
+        .collect();
+    let encoded = encode_track(&track, 10usize).unwrap();
+    assert_eq!(encoded.len(), track.len());
+    assert_eq!(encoded[0].0, 0);
+    assert!(encoded[1..].iter().all(|(shared, _)| *shared >= 6));
+
+    let hashes: Vec<String> = track.iter().map(|&c| encode(c, 10usize).unwrap()).collect();
+    assert_eq!(decode_track(&encoded).unwrap(), hashes);
+
+    // a repeated point is stored as an empty suffix
+    let still = encode_track(&[track[0], track[0]], 10usize).unwrap();
+    assert_eq!(still[1], (10, String::new()));
+
+    assert!(encode_track(&[Coordinate { x: 190f64, y: 0f64 }], 3usize).is_err());
+    assert!(decode_track(&[(1, "e7".to_string())]).is_err());
+    assert!(decode_track(&[(0, "e7".to_string()), (3, "1".to_string())]).is_err());
+    assert!(decode_track(&[(0, "zz".to_string())]).is_err());
+    assert!(decode_track(&[(0, "e7".to_string()), (1, "1g".to_string())]).is_err());
+    assert!(decode_track(&[(0, "é".to_string()), (1, "1".to_string())]).is_err());
+    assert!(decode_track(&[(0, "e7".to_string()), (1, "é".to_string())]).is_err());
+    assert_eq!(encode_track(&[], 3usize).unwrap(), vec![]);
+}
+
+#[test]
+fn test_adjacency_mask() {
+    let ns = neighbors("e71150dc99").unwrap();
+    let all: HashSet<String> = vec![ns.sw, ns.s, ns.se, ns.w, ns.e, ns.nw, ns.n, ns.ne]
+        .into_iter()
+        .collect();
+    assert_eq!(adjacency_mask("e71150dc99", &all).unwrap(), 0xff);
+    assert_eq!(adjacency_mask("e71150dc99", &HashSet::new()).unwrap(), 0);
+
+    for (i, &direction) in Direction::ALL.iter().enumerate() {
+        assert_eq!(direction.bit(), 1 << i);
+        let one: HashSet<String> = vec![neighbor("e71150dc99", direction).unwrap()]
+            .into_iter()
+            .collect();
+        assert_eq!(adjacency_mask("e71150dc99", &one).unwrap(), direction.bit());
+    }
+
+    // the cell itself is not its own neighbor
+    let own: HashSet<String> = vec!["e71150dc99".to_string()].into_iter().collect();
+    assert_eq!(adjacency_mask("e71150dc99", &own).unwrap(), 0);
+
+    // no southern neighbors on the bottom row
+    let bottom: HashSet<String> = vec!["1".to_string(), "2".to_string(), "3".to_string()]
+        .into_iter()
+        .collect();
+    assert_eq!(
+        adjacency_mask("0", &bottom).unwrap(),
+        Direction::N.bit() | Direction::NE.bit() | Direction::E.bit()
+    );
+
+    assert!(adjacency_mask("wwgj", &all).is_err());
+}
+
+#[test]
+fn test_adjacency() {
+    let hash = "e71150dc99";
+    let found = neighbors(hash).unwrap();
+    let expected = [
+        (&found.n, Direction::N),
+        (&found.ne, Direction::NE),
+        (&found.e, Direction::E),
+        (&found.se, Direction::SE),
+        (&found.s, Direction::S),
+        (&found.sw, Direction::SW),
+        (&found.w, Direction::W),
+        (&found.nw, Direction::NW),
+    ];
+    for &(neighbor, direction) in expected.iter() {
+        assert_eq!(adjacency(hash, neighbor).unwrap(), Some(direction));
+        assert_eq!(
+            adjacency(&neighbor.to_uppercase(), hash).unwrap(),
+            Some(match direction {
+                Direction::N => Direction::S,
+                Direction::NE => Direction::SW,
+                Direction::E => Direction::W,
+                Direction::SE => Direction::NW,
+                Direction::S => Direction::N,
+                Direction::SW => Direction::NE,
+                Direction::W => Direction::E,
+                Direction::NW => Direction::SE,
+            })
+        );
+    }
+
+    assert_eq!(adjacency(hash, hash).unwrap(), None);
+    assert_eq!(adjacency(hash, "e71150dc").unwrap(), None);
+    assert_eq!(adjacency(hash, &ring(hash, 2).unwrap()[0]).unwrap(), None);
+
+    // Across the equator and prime meridian, but not the antimeridian
+    assert_eq!(adjacency("3", "c").unwrap(), Some(Direction::NE));
+    assert_eq!(adjacency("5", "4").unwrap(), Some(Direction::S));
+    assert_eq!(adjacency("5", "f").unwrap(), None);
+
+    assert!(adjacency("wwgj", hash).is_err());
+    assert!(adjacency(hash, "wwgj").is_err());
+}
+
+#[test]
+fn test_is_edge_cell() {
+    assert_eq!(is_edge_cell("0").unwrap(), Some(Direction::SW));
+    assert_eq!(is_edge_cell("a").unwrap(), Some(Direction::SE));
+    assert_eq!(is_edge_cell("5").unwrap(), Some(Direction::NW));
+    assert_eq!(is_edge_cell("f").unwrap(), Some(Direction::NE));
+    assert_eq!(is_edge_cell("").unwrap(), Some(Direction::NE));
+
+    let bottom = encode(
+        Coordinate {
+            x: 0.5f64,
+            y: -90f64,
+        },
+        8usize,
+    )
+    .unwrap();
+    let top = encode(
+        Coordinate {
+            x: 0.5f64,
+            y: 90f64,
+        },
+        8usize,
+    )
+    .unwrap();
+    let west = encode(
+        Coordinate {
+            x: -180f64,
+            y: 10f64,
+        },
+        8usize,
+    )
+    .unwrap();
+    let east = encode(
+        Coordinate {
+            x: 180f64,
+            y: 10f64,
+        },
+        8usize,
+    )
+    .unwrap();
+    assert_eq!(is_edge_cell(&bottom).unwrap(), Some(Direction::S));
+    assert_eq!(is_edge_cell(&top).unwrap(), Some(Direction::N));
+    assert_eq!(is_edge_cell(&west).unwrap(), Some(Direction::W));
+    assert_eq!(is_edge_cell(&east).unwrap(), Some(Direction::E));
+
+    // no neighbors beyond the poles
+    assert!(neighbor(&bottom, Direction::S).is_err());
+    assert!(neighbor(&top, Direction::N).is_err());
+
+    assert_eq!(is_edge_cell("e71150dc99").unwrap(), None);
+    assert!(is_edge_cell("wwgj").is_err());
+}
+
+#[test]
+fn test_quantization_error() {
+    let c = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    for len in 1..12usize {
+        let (lon, lat) = quantization_error(c, len).unwrap();
+        let (width, height) = cell_dimensions(&encode(c, len).unwrap()).unwrap();
+        // never more than half the cell, give or take the latitude the
+        // width is measured at
+        assert!(lon <= width * 0.51f64, "{}: {} > {}", len, lon, width);
+        assert!(
+            lat <= height * 0.5f64 + 1e-6f64,
+            "{}: {} > {}",
+            len,
+            lat,
+            height
+        );
+    }
+
+    let (center, _, _) = decode("e71150dc99").unwrap();
+    assert_eq!(quantization_error(center, 10usize).unwrap(), (0f64, 0f64));
+
+    assert!(quantization_error(Coordinate { x: 190f64, y: 0f64 }, 3usize).is_err());
+}
+
+#[test]
+fn test_connected_components() {
+    // two blobs, plus a cell touching the second one only at a corner
+    let rect = decode_bbox("e71150").unwrap();
+    let blob = cover_bbox(&rect, 8usize).unwrap();
+    let far = cover_bbox(&decode_bbox("e7115f").unwrap(), 8usize).unwrap();
+    let corner = neighbor(far.last().unwrap(), Direction::NE).unwrap();
+
+    let mut cells: Vec<&str> = blob.iter().map(|cell| cell.as_str()).collect();
+    cells.extend(far.iter().map(|cell| cell.as_str()));
+    cells.push(&corner);
+    cells.push(&blob[0]);
+
+    let four = connected_components(&cells, Connectivity::Four).unwrap();
+    assert_eq!(four.len(), 3);
+    assert_eq!(four[0].len(), blob.len());
+    assert_eq!(four[1].len(), far.len());
+    assert_eq!(four[2], vec![corner.clone()]);
+
+    let eight = connected_components(&cells, Connectivity::Eight).unwrap();
+    assert_eq!(eight.len(), 2);
+    assert_eq!(eight[1].len(), far.len() + 1);
+    assert!(eight[1].contains(&corner));
+
+    let mut sorted = blob.clone();
+    sorted.sort();
+    assert_eq!(eight[0], sorted);
+
+    assert_eq!(
+        connected_components(&[], Connectivity::Eight)
+            .unwrap()
+            .len(),
+        0
+    );
+    assert!(connected_components(&["e7", "wwgj"], Connectivity::Four).is_err());
+
+    // Uppercase cells connect like their lowercase forms
+    assert_eq!(
+        connected_components(&["E7", "e6", "E5", "5"], Connectivity::Four).unwrap(),
+        vec![vec!["e5", "e6", "e7"], vec!["5"]]
+    );
+}
+
+#[test]
+fn test_grid_walker() {
+    let rect = Rect {
+        min: Coordinate {
+            x: 112.5f64,
+            y: 37.8f64,
+        },
+        max: Coordinate {
+            x: 113.1f64,
+            y: 38.2f64,
+        },
+    };
+    let cells = cover_bbox(&rect, 5usize).unwrap();
+    let (_, cols, _) = grid(5usize, &rect).unwrap().last().unwrap();
+    let walked: Vec<String> = GridWalker::new(
+        &cells[0],
+        cols as usize + 1,
+        cells.len() / (cols as usize + 1),
+    )
+    .unwrap()
+    .collect();
+    assert_eq!(walked, cells);
+
+    // Stepping matches neighbor
+    let mut walker = GridWalker::new("e7115", 2, 2).unwrap();
+    let start = walker.next().unwrap();
+    assert_eq!(start, "e7115");
+    assert_eq!(
+        walker.next().unwrap(),
+        neighbor(&start, Direction::E).unwrap()
+    );
+    assert_eq!(
+        walker.next().unwrap(),
+        neighbor(&start, Direction::N).unwrap()
+    );
+    assert_eq!(
+        walker.next().unwrap(),
+        neighbor(&start, Direction::NE).unwrap()
+    );
+    assert_eq!(walker.next(), None);
+
+    // Cells past the antimeridian and the north pole are left out
+    assert_eq!(GridWalker::new("f", 2, 2).unwrap().count(), 1);
+    assert_eq!(GridWalker::new("e", 3, 0).unwrap().count(), 0);
+    assert!(GridWalker::new("e711z", 2, 2).is_err());
+}
+
+#[test]
+fn test_cell_bounds_exact() {
+    let hash = encode(
+        Coordinate {
+            x: 112.5584f64,
+            y: 37.8324f64,
+        },
+        14usize,
+    )
+    .unwrap();
+    let cells: Vec<String> = GridWalker::new(&hash, 3, 3).unwrap().collect();
+    for (i, cell) in cells.iter().enumerate() {
+        let exact = cell_bounds_exact(cell).unwrap();
+        let bbox = decode_bbox(cell).unwrap();
+        assert!((exact.min.x - bbox.min.x).abs() < 1e-9);
+        assert!((exact.max.y - bbox.max.y).abs() < 1e-9);
+        if i % 3 < 2 {
+            assert_eq!(exact.max.x, cell_bounds_exact(&cells[i + 1]).unwrap().min.x);
+        }
+        if i < 6 {
+            assert_eq!(exact.max.y, cell_bounds_exact(&cells[i + 3]).unwrap().min.y);
+        }
+    }
+
+    let world = cell_bounds_exact("").unwrap();
+    assert_eq!((world.min.x, world.min.y), (-180f64, -90f64));
+    assert_eq!((world.max.x, world.max.y), (180f64, 90f64));
+    assert!(cell_bounds_exact("e71150dc9e71150dc").is_err());
+}
+
+#[test]
+fn test_encode_with_offset() {
+    let c = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    let (hash, x, y) = encode_with_offset(c, 6usize).unwrap();
+    assert_eq!(hash, encode(c, 6usize).unwrap());
+    assert!(x > 0f64 && x < 1f64 && y > 0f64 && y < 1f64);
+
+    let rect = decode_bbox(&hash).unwrap();
+    let rebuilt_x = rect.min.x + x * (rect.max.x - rect.min.x);
+    let rebuilt_y = rect.min.y + y * (rect.max.y - rect.min.y);
+    assert!((rebuilt_x - c.x).abs() < 1e-9);
+    assert!((rebuilt_y - c.y).abs() < 1e-9);
+
+    // A point on a boundary belongs to the cell west and south of it
+    let (hash, x, y) = encode_with_offset(Coordinate { x: 0f64, y: 0f64 }, 1usize).unwrap();
+    assert_eq!((hash.as_str(), x, y), ("3", 1f64, 1f64));
+    let (hash, x, y) = encode_with_offset(Coordinate { x: 0f64, y: 0f64 }, 3usize).unwrap();
+    assert_eq!((hash.as_str(), x, y), ("3ff", 1f64, 1f64));
+
+    // The range is closed: the east and north edges of the world give 1
+    let (hash, x, y) = encode_with_offset(
+        Coordinate {
+            x: 180f64,
+            y: 90f64,
+        },
+        3usize,
+    )
+    .unwrap();
+    assert_eq!((hash.as_str(), x, y), ("fff", 1f64, 1f64));
+    let (_, x, y) = encode_with_offset(
+        Coordinate {
+            x: 180f64,
+            y: 10f64,
+        },
+        3usize,
+    )
+    .unwrap();
+    assert!(x == 1f64 && (0f64..1f64).contains(&y));
+
+    let (_, x, y) = encode_with_offset(
+        Coordinate {
+            x: -180f64,
+            y: -90f64,
+        },
+        3usize,
+    )
+    .unwrap();
+    assert_eq!((x, y), (0f64, 0f64));
+    assert!(encode_with_offset(Coordinate { x: 181f64, y: 0f64 }, 3usize).is_err());
+}
+
+#[test]
+fn test_cover_circle_weighted() {
+    let center = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    let cells = cover_circle_weighted(center, 5000f64, 6usize).unwrap();
+    let hash = encode(center, 6usize).unwrap();
+    assert!(cells.iter().any(|(cell, _)| *cell == hash));
+
+    // The weighted area approximates the circle's
+    let area: f64 = cells
+        .iter()
+        .map(|(cell, weight)| {
+            let (width, height) = cell_dimensions(cell).unwrap();
+            width * height * weight
+        })
+        .sum();
+    let expected = std::f64::consts::PI * 5000f64 * 5000f64;
+    assert!(
+        (area - expected).abs() / expected < 0.05,
+        "{} vs {}",
+        area,
+        expected
+    );
+
+    // A circle well inside one cell weighs a small fraction of it
+    let small = cover_circle_weighted(center, 10f64, 3usize).unwrap();
+    assert_eq!(small.len(), 1);
+    assert!(small[0].1 > 0f64 && small[0].1 < 0.05);
+
+    assert!(cover_circle_weighted(Coordinate { x: 200f64, y: 0f64 }, 10f64, 3usize).is_err());
+    let err = cover_circle_weighted(center, -1f64, 6usize).unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        "invalid radius: -1, expected a finite non-negative distance in meters"
+    );
+    assert!(cover_circle_weighted(center, f64::NAN, 6usize).is_err());
+    assert!(cover_circle_weighted(center, f64::INFINITY, 6usize).is_err());
+    assert!(cover_circle_weighted(center, 0f64, 6usize).is_ok());
+}
+
+#[test]
+fn test_extension_traits() {
+    let c = Coordinate {
+        x: 117.02636f64,
+        y: 32.01416f64,
+    };
+    assert_eq!(c.geohash(8usize).unwrap(), encode(c, 8usize).unwrap());
+    assert_eq!(
+        Point(c).geohash(8usize).unwrap(),
+        encode(c, 8usize).unwrap()
+    );
+    assert!(Coordinate { x: 181f64, y: 0f64 }.geohash(8usize).is_err());
+
+    let hash = c.geohash(8usize).unwrap();
+    assert_eq!(hash.decode().unwrap(), decode(&hash).unwrap());
+    assert_eq!(
+        hash.as_str().neighbors().unwrap(),
+        neighbors(&hash).unwrap()
+    );
+    assert!("e711z".decode().is_err());
+
+    let rect = decode_bbox(&hash).unwrap();
+    let array = rect.to_array();
+    assert_eq!(array, [rect.min.x, rect.min.y, rect.max.x, rect.max.y]);
+    assert_eq!(Rect::from_array(array), rect);
+    assert_eq!(
+        Rect::from_array([-180f64, -90f64, 180f64, 90f64]),
+        decode_bbox("").unwrap()
+    );
+}
+
+#[test]
+fn test_decode_with_offset() {
+    let coords = [
+        (112.5584f64, 37.8324f64),
+        (117.02636f64, 32.01416f64),
+        (-120.6623f64, 35.3003f64),
+        (-180f64, -90f64),
+        (180f64, 90f64),
+    ];
+    for &(x, y) in coords.iter() {
+        for len in 1..13 {
+            let c = Coordinate { x, y };
+            let (hash, ox, oy) = encode_with_offset(c, len).unwrap();
+            let rebuilt = decode_with_offset(&hash, ox, oy).unwrap();
+            assert!((rebuilt.x - x).abs() < 1e-9, "{:?} at {}", rebuilt, len);
+            assert!((rebuilt.y - y).abs() < 1e-9, "{:?} at {}", rebuilt, len);
+        }
+    }
+
+    let (center, _, _) = decode("e71150").unwrap();
+    assert_eq!(decode_with_offset("e71150", 0.5, 0.5).unwrap(), center);
+    let err = decode_with_offset("e71150", 1.5, 0.5).unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        "invalid cell offset: 1.5, expected a value in [0, 1]"
+    );
+    assert!(decode_with_offset("e711z", 0.5, 0.5).is_err());
+}
+
+#[test]
+fn test_ascii_map() {
+    // Shrunk so the cells west and south of "e7" are left out
+    let rect = decode_bbox("e7").unwrap().buffer(-1e-9, -1e-9);
+    let marked: HashSet<String> = ["e70", "e75", "e7f"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let map = ascii_map(&rect, 3usize, &marked).unwrap();
+    let lines: Vec<&str> = map.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines.iter().all(|line| line.len() == 4));
+    assert_eq!(map.matches('#').count(), 3);
+    // "e70" is the south-west cell and "e7f" the north-east one
+    assert_eq!(&lines[3][..1], "#");
+    assert_eq!(&lines[0][3..], "#");
+
+    assert_eq!(ascii_map(&rect, 2usize, &HashSet::new()).unwrap(), ".");
+    assert!(ascii_map(&rect, 17usize, &marked).is_err());
+}
+
+#[test]
+fn test_decode_error_per_length() {
+    // Every character holds two longitude and two latitude bits, so both axes
+    // are always split the same number of times and the longitude error stays
+    // exactly twice the latitude error.
+    for len in 1..13 {
+        let hash = encode(
+            Coordinate {
+                x: 112.5584f64,
+                y: 37.8324f64,
+            },
+            len,
+        )
+        .unwrap();
+        let (_, lon_err, lat_err) = decode(&hash).unwrap();
+        let splits = 4f64.powi(len as i32);
+        assert_eq!(lon_err, 180f64 / splits, "length {}", len);
+        assert_eq!(lat_err, 90f64 / splits, "length {}", len);
+        assert_eq!(lon_err / lat_err, 2f64, "length {}", len);
+    }
+}
+
+#[test]
+fn test_snap() {
+    let a = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    let b = Coordinate {
+        x: 112.5585f64,
+        y: 37.8325f64,
+    };
+    let (center, _, _) = decode(&encode(a, 6usize).unwrap()).unwrap();
+    assert_eq!(snap(a, 6usize).unwrap(), center);
+    assert_eq!(snap(a, 6usize).unwrap(), snap(b, 6usize).unwrap());
+    assert_ne!(snap(a, 12usize).unwrap(), snap(b, 12usize).unwrap());
+
+    let snapped = snap_all(&[a, b], 6usize).unwrap();
+    assert_eq!(snapped, vec![center, center]);
+    assert!(snap_all(&[a, Coordinate { x: 181f64, y: 0f64 }], 6usize).is_err());
+}
+
+#[test]
+fn test_cell_circle_overlap() {
+    let hash = "e71150";
+    let (center, _, _) = decode(hash).unwrap();
+    let (width, height) = cell_dimensions(hash).unwrap();
+
+    // A circle well inside the cell covers its share of the area
+    let radius = width.min(height) / 4f64;
+    let expected = PI * radius * radius / (width * height);
+    let fraction = cell_circle_overlap(hash, center, radius).unwrap();
+    assert!(
+        (fraction - expected).abs() < 0.02,
+        "{} vs {}",
+        fraction,
+        expected
+    );
+
+    // Growing the circle never shrinks the overlap
+    let mut previous = 0f64;
+    for step in 1..20 {
+        let fraction = cell_circle_overlap(hash, center, 500f64 * f64::from(step)).unwrap();
+        assert!(fraction >= previous);
+        assert!((0f64..=1f64).contains(&fraction));
+        previous = fraction;
+    }
+    assert_eq!(previous, 1f64);
+
+    // A tiny circle falls back to the ratio of the areas
+    let tiny = cell_circle_overlap(hash, center, 10f64).unwrap();
+    compare_within(tiny, PI * 100f64 / (width * height), 1e-12f64);
+    let elsewhere = decode("e72").unwrap().0;
+    assert_eq!(cell_circle_overlap(hash, elsewhere, 10f64).unwrap(), 0f64);
+
+    // Consistent with the weights of `cover_circle_weighted`
+    for (cell, weight) in cover_circle_weighted(center, 5000f64, 7).unwrap() {
+        assert_eq!(cell_circle_overlap(&cell, center, 5000f64).unwrap(), weight);
+    }
+
+    assert!(cell_circle_overlap("e7g", center, 10f64).is_err());
+    assert!(cell_circle_overlap(hash, Coordinate { x: 190f64, y: 0f64 }, 10f64).is_err());
+    let err = cell_circle_overlap(hash, center, -1f64).unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        "invalid radius: -1, expected a finite non-negative distance in meters"
+    );
+    assert!(cell_circle_overlap(hash, center, f64::NAN).is_err());
+    assert!(cell_circle_overlap(hash, center, f64::INFINITY).is_err());
+}
+
+#[test]
+fn test_cover_buffered_path() {
+    let route = [
+        Coordinate {
+            x: 112.50f64,
+            y: 37.80f64,
+        },
+        Coordinate {
+            x: 112.56f64,
+            y: 37.83f64,
+        },
+        Coordinate {
+            x: 112.60f64,
+            y: 37.90f64,
+        },
+    ];
+    let corridor = cover_buffered_path(&route, 500f64, 6).unwrap();
+    let mut sorted = corridor.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(corridor, sorted);
+
+    // Every cell the path passes through is in, even without a buffer
+    let line = cover_buffered_path(&route, 0f64, 6).unwrap();
+    for pair in route.windows(2) {
+        for step in 0..=100 {
+            let t = f64::from(step) / 100f64;
+            let c = Coordinate {
+                x: pair[0].x + (pair[1].x - pair[0].x) * t,
+                y: pair[0].y + (pair[1].y - pair[0].y) * t,
+            };
+            let hash = encode(c, 6).unwrap();
+            assert!(line.contains(&hash), "{} at {:?}", hash, c);
+        }
+    }
+    assert!(line.iter().all(|hash| corridor.contains(hash)));
+    assert!(corridor.len() > line.len());
+
+    // Cell centers within the buffer are in, those further than the radius
+    // plus half a cell diagonal (about 1.1km at length 7) are out
+    let corridor = cover_buffered_path(&route, 500f64, 7).unwrap();
+    for hash in cover_bbox(&decode_bbox("e711").unwrap().buffer(0.1, 0.1), 7).unwrap() {
+        let (center, _, _) = decode(&hash).unwrap();
+        let distance = route
+            .windows(2)
+            .map(|pair| {
+                (0..=1000)
+                    .map(|step| {
+                        let t = f64::from(step) / 1000f64;
+                        let p = Coordinate {
+                            x: pair[0].x + (pair[1].x - pair[0].x) * t,
+                            y: pair[0].y + (pair[1].y - pair[0].y) * t,
+                        };
+                        let (dx, dy) = (
+                            (p.x - center.x).to_radians() * center.y.to_radians().cos(),
+                            (p.y - center.y).to_radians(),
+                        );
+                        6_371_008.8 * dx.hypot(dy)
+                    })
+                    .fold(f64::INFINITY, f64::min)
+            })
+            .fold(f64::INFINITY, f64::min);
+        if distance < 450f64 {
+            assert!(corridor.contains(&hash), "{} at {}m", hash, distance);
+        } else if distance > 1700f64 {
+            assert!(!corridor.contains(&hash), "{} at {}m", hash, distance);
+        }
+    }
+
+    // A single point is covered like a circle
+    let circle: Vec<String> = cover_circle_weighted(route[0], 500f64, 6)
+        .unwrap()
+        .into_iter()
+        .map(|(hash, _)| hash)
+        .collect();
+    let mut point = cover_buffered_path(&route[..1], 500f64, 6).unwrap();
+    point.retain(|hash| !circle.contains(hash));
+    assert!(point.is_empty(), "{:?}", point);
+
+    assert!(cover_buffered_path(&[], 500f64, 6).is_err());
+    assert!(cover_buffered_path(&route, 500f64, 17).is_err());
+    assert!(cover_buffered_path(&route, 500f64, 0).is_err());
+    assert!(cover_buffered_path(&[Coordinate { x: 190f64, y: 0f64 }], 500f64, 6).is_err());
+}
+
+#[test]
+fn test_cover_polygon_outline() {
+    let polygon = [
+        Coordinate {
+            x: 112.1f64,
+            y: 37.6f64,
+        },
+        Coordinate {
+            x: 113.4f64,
+            y: 37.7f64,
+        },
+        Coordinate {
+            x: 112.9f64,
+            y: 38.4f64,
+        },
+        Coordinate {
+            x: 112.6f64,
+            y: 37.9f64,
+        },
+    ];
+    let cells = cover_polygon_outline(&polygon, 5usize).unwrap();
+    let mut sorted = cells.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(cells, sorted);
+
+    for (i, &a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        for step in 0..=100 {
+            let t = step as f64 / 100f64;
+            let c = Coordinate {
+                x: a.x + (b.x - a.x) * t,
+                y: a.y + (b.y - a.y) * t,
+            };
+            let hash = encode(c, 5usize).unwrap();
+            assert!(cells.contains(&hash), "{} not in {:?}", hash, cells);
+        }
+    }
+    // The outline leaves the middle of the polygon out
+    let inside = encode(
+        Coordinate {
+            x: 112.9f64,
+            y: 37.85f64,
+        },
+        5usize,
+    )
+    .unwrap();
+    assert!(!cells.contains(&inside));
+
+    let point = cover_polygon_outline(&polygon[..1], 5usize).unwrap();
+    assert_eq!(point, vec![encode(polygon[0], 5usize).unwrap()]);
+    assert!(cover_polygon_outline(&[], 5usize).is_err());
+    assert!(cover_polygon_outline(&polygon, 17usize).is_err());
+    assert!(cover_polygon_outline(&polygon, 0usize).is_err());
+    assert!(cover_polygon(&polygon, 0usize).is_err());
+}
+
+#[test]
+fn test_geohash_conversions() {
+    let hash = Geohash::try_from("e71150dc9").unwrap();
+    assert_eq!(hash, Geohash::try_from("e71150dc9".to_string()).unwrap());
+    assert_eq!(hash.as_ref(), "e71150dc9");
+    assert_eq!(hash.len(), 9);
+    assert_eq!(hash.to_string(), "e71150dc9");
+    assert_eq!(decode(&hash).unwrap(), decode("e71150dc9").unwrap());
+    assert_eq!(hash.clone().into_string(), "e71150dc9");
+
+    // Uppercase is accepted as `decode` accepts it
+    // Case is normalized, so equal cells compare and hash equal
+    let upper = Geohash::try_from("E71150").unwrap();
+    assert_eq!(upper.as_ref(), "e71150");
+    assert_eq!(upper, Geohash::try_from("e71150").unwrap());
+    let set: HashSet<Geohash> = vec![upper, Geohash::try_from("e71150").unwrap()]
+        .into_iter()
+        .collect();
+    assert_eq!(set.len(), 1);
+    assert!(Geohash::try_from("").is_ok());
+    let err = Geohash::try_from("e711z").unwrap_err();
+    assert!(err.downcast_ref::<GeohashError>().is_some());
+}
+
+#[test]
+fn test_cover_polygon() {
+    // A rectangle covers the same cells as its bounding box
+    let rect = Rect {
+        min: Coordinate {
+            x: 112.13f64,
+            y: 37.61f64,
+        },
+        max: Coordinate {
+            x: 113.37f64,
+            y: 38.29f64,
+        },
+    };
+    let square = [
+        rect.min,
+        Coordinate {
+            x: rect.max.x,
+            y: rect.min.y,
+        },
+        rect.max,
+        Coordinate {
+            x: rect.min.x,
+            y: rect.max.y,
+        },
+    ];
+    let mut expected = cover_bbox(&rect, 5usize).unwrap();
+    expected.sort();
+    assert_eq!(cover_polygon(&square, 5usize).unwrap(), expected);
+
+    // A U shape leaves out the cells in its notch
+    let u = [
+        Coordinate {
+            x: 112.0f64,
+            y: 37.0f64,
+        },
+        Coordinate {
+            x: 114.0f64,
+            y: 37.0f64,
+        },
+        Coordinate {
+            x: 114.0f64,
+            y: 39.0f64,
+        },
+        Coordinate {
+            x: 113.6f64,
+            y: 39.0f64,
+        },
+        Coordinate {
+            x: 113.6f64,
+            y: 37.4f64,
+        },
+        Coordinate {
+            x: 112.4f64,
+            y: 37.4f64,
+        },
+        Coordinate {
+            x: 112.4f64,
+            y: 39.0f64,
+        },
+        Coordinate {
+            x: 112.0f64,
+            y: 39.0f64,
+        },
+    ];
+    let cells = cover_polygon(&u, 5usize).unwrap();
+    let notch = encode(
+        Coordinate {
+            x: 113.0f64,
+            y: 38.5f64,
+        },
+        5usize,
+    )
+    .unwrap();
+    let arm = encode(
+        Coordinate {
+            x: 112.2f64,
+            y: 38.5f64,
+        },
+        5usize,
+    )
+    .unwrap();
+    let base = encode(
+        Coordinate {
+            x: 113.0f64,
+            y: 37.2f64,
+        },
+        5usize,
+    )
+    .unwrap();
+    assert!(!cells.contains(&notch));
+    assert!(cells.contains(&arm));
+    assert!(cells.contains(&base));
+    for cell in cover_polygon_outline(&u, 5usize).unwrap() {
+        assert!(cells.contains(&cell));
+    }
+
+    assert!(cover_polygon(&[], 4usize).is_err());
+}
+
+#[test]
+fn test_bearing() {
+    let origin = encode(
+        Coordinate {
+            x: 0.5f64,
+            y: 0.5f64,
+        },
+        8usize,
+    )
+    .unwrap();
+    let east = neighbor(&origin, Direction::E).unwrap();
+    let north = neighbor(&origin, Direction::N).unwrap();
+    let sw = neighbor(&origin, Direction::SW).unwrap();
+    assert!((bearing(&origin, &east).unwrap() - 90f64).abs() < 1e-3);
+    assert!(bearing(&origin, &north).unwrap().abs() < 1e-9);
+    let to_sw = bearing(&origin, &sw).unwrap();
+    assert!(to_sw > 180f64 && to_sw < 270f64, "{}", to_sw);
+
+    // Across the antimeridian the short way round is east
+    let west_edge = encode(
+        Coordinate {
+            x: 179.9f64,
+            y: 10f64,
+        },
+        6usize,
+    )
+    .unwrap();
+    let east_edge = encode(
+        Coordinate {
+            x: -179.9f64,
+            y: 10f64,
+        },
+        6usize,
+    )
+    .unwrap();
+    let across = bearing(&west_edge, &east_edge).unwrap();
+    assert!(across > 45f64 && across < 135f64, "{}", across);
+
+    assert_eq!(bearing(&origin, &origin).unwrap(), 0f64);
+    assert!(bearing("e711z", &origin).is_err());
+}
+
+#[test]
+fn test_grid_coords() {
+    assert_eq!(grid_coords("").unwrap(), (0, 0));
+    assert_eq!(grid_coords("1").unwrap(), (0, 1));
+    assert_eq!(grid_coords("2").unwrap(), (1, 0));
+    assert_eq!(grid_coords("E7").unwrap(), (13, 11));
+    assert_eq!(
+        grid_coords("ffffffffffffffff").unwrap(),
+        (u32::MAX, u32::MAX)
+    );
+
+    // Columns grow eastwards and rows northwards
+    let (col, row) = grid_coords("e71150dc").unwrap();
+    let (east_col, east_row) = grid_coords(&neighbor("e71150dc", Direction::E).unwrap()).unwrap();
+    assert_eq!((east_col, east_row), (col + 1, row));
+    let (north_col, north_row) = grid_coords(&neighbor("e71150dc", Direction::N).unwrap()).unwrap();
+    assert_eq!((north_col, north_row), (col, row + 1));
+
+    assert!(grid_coords("fffffffffffffffff").is_err());
+    assert!(grid_coords("e7g").is_err());
+}
+
+#[test]
+fn test_from_grid_coords() {
+    for hash in ["0", "e7", "E71150DC", "4d8c0f1817", "ffffffffffffffff"].iter() {
+        let (col, row) = grid_coords(hash).unwrap();
+        assert_eq!(
+            from_grid_coords(col, row, hash.len()).unwrap(),
+            hash.to_lowercase()
+        );
+    }
+
+    // Walking the grid by index matches walking it by cell
+    let by_index: Vec<String> = (0..3)
+        .flat_map(|row| (0..4).map(move |col| from_grid_coords(col, row, 1).unwrap()))
+        .collect();
+    let walked: Vec<String> = GridWalker::new("0", 4, 3).unwrap().collect();
+    assert_eq!(by_index, walked);
+
+    let err = from_grid_coords(0, 16, 2).unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        "grid coordinates out of range: (0, 16) at length 2"
+    );
+    assert!(from_grid_coords(0, 0, 0).is_err());
+    assert!(from_grid_coords(0, 0, 17).is_err());
+}
+
+#[test]
+fn test_hilbert_index() {
+    // The order-4 Hilbert curve, from the south-west to the south-east cell
+    let curve = [
+        "0", "2", "3", "1", "4", "5", "7", "6", "c", "d", "f", "e", "b", "9", "8", "a",
+    ];
+    for (d, hash) in curve.iter().enumerate() {
+        assert_eq!(hilbert_index(hash).unwrap(), d as u64, "{}", hash);
+    }
+
+    // Consecutive indices are always edge neighbors
+    let mut cells: Vec<(u64, String)> = GridWalker::new("00", 16, 16)
+        .unwrap()
+        .map(|hash| (hilbert_index(&hash).unwrap(), hash))
+        .collect();
+    cells.sort();
+    for (d, pair) in cells.windows(2).enumerate() {
+        assert_eq!(pair[0].0, d as u64);
+        let edge_neighbors: Vec<String> = [Direction::N, Direction::E, Direction::S, Direction::W]
+            .iter()
+            .filter_map(|&direction| neighbor(&pair[0].1, direction).ok())
+            .collect();
+        assert!(edge_neighbors.contains(&pair[1].1), "{:?}", pair);
+    }
+
+    assert_eq!(hilbert_index("").unwrap(), 0);
+    assert!(hilbert_index("e711z").is_err());
+}
+
+#[test]
+fn test_interpolate_cells() {
+    let a = "e71150dc9";
+    let b = encode(
+        Coordinate {
+            x: 117.02636f64,
+            y: 32.01416f64,
+        },
+        9usize,
+    )
+    .unwrap();
+    assert_eq!(interpolate_cells(a, &b, 0f64, 6usize).unwrap(), &a[..6]);
+    assert_eq!(interpolate_cells(a, &b, 1f64, 6usize).unwrap(), &b[..6]);
+
+    // The halfway cell lies about as far from both ends
+    let mid = interpolate_cells(a, &b, 0.5, 9usize).unwrap();
+    let (c, _, _) = decode(&mid).unwrap();
+    let (ca, _, _) = decode(a).unwrap();
+    let (cb, _, _) = decode(&b).unwrap();
+    let angle = |p: Coordinate<f64>, q: Coordinate<f64>| {
+        let (p_lat, q_lat) = (p.y.to_radians(), q.y.to_radians());
+        let cos =
+            p_lat.sin() * q_lat.sin() + p_lat.cos() * q_lat.cos() * (q.x - p.x).to_radians().cos();
+        cos.acos().to_degrees()
+    };
+    let (da, db) = (angle(c, ca), angle(c, cb));
+    assert!((da - db).abs() < 0.001, "{} vs {}", da, db);
+
+    // Across the antimeridian the path goes the short way round
+    let west = encode(
+        Coordinate {
+            x: 179f64,
+            y: 10f64,
+        },
+        6usize,
+    )
+    .unwrap();
+    let east = encode(
+        Coordinate {
+            x: -179f64,
+            y: 10f64,
+        },
+        6usize,
+    )
+    .unwrap();
+    let (c, _, _) = decode(&interpolate_cells(&west, &east, 0.5, 6usize).unwrap()).unwrap();
+    assert!(c.x.abs() > 179f64, "{:?}", c);
+
+    // Opposite centers fall back to a straight line in degrees
+    assert_eq!(interpolate_cells("0", "d", 0f64, 1usize).unwrap(), "0");
+    assert_eq!(interpolate_cells("0", "d", 1f64, 1usize).unwrap(), "d");
+    assert!(interpolate_cells("e711z", a, 0.5, 6usize).is_err());
+}
+
+#[test]
+fn test_downsample() {
+    let hashes = [
+        "e71150dc9",
+        "e71150dcb",
+        "e7120aaaa",
+        "e71150dc9",
+        "E7120bbbb",
+    ];
+    let groups = downsample(&hashes, 5usize).unwrap();
+    let mut expected: HashMap<String, Vec<String>> = HashMap::new();
+    expected.insert(
+        "e7115".to_string(),
+        vec![
+            "e71150dc9".to_string(),
+            "e71150dcb".to_string(),
+            "e71150dc9".to_string(),
+        ],
+    );
+    expected.insert(
+        "e7120".to_string(),
+        vec!["e7120aaaa".to_string(), "E7120bbbb".to_string()],
+    );
+    assert_eq!(groups, expected);
+
+    assert_eq!(downsample(&hashes, 0usize).unwrap()[""].len(), 5);
+    assert!(downsample(&["e711"], 5usize).is_err());
+    assert!(downsample(&["e711zaaaa"], 5usize).is_err());
+}
+
+#[test]
+fn test_color_for() {
+    assert_eq!(color_for("e71150dc9"), color_for("e71150dc9"));
+    assert_eq!(color_for("e71150dc9"), color_for("E71150DC9"));
+
+    // Neighbors get clearly different colors
+    let hash = "e71150dc9";
+    let color = color_for(hash);
+    for n in ring(hash, 1).unwrap() {
+        let other = color_for(&n);
+        let diff: i32 = color
+            .iter()
+            .zip(other.iter())
+            .map(|(&a, &b)| (i32::from(a) - i32::from(b)).abs())
+            .sum();
+        assert!(diff > 30, "{} {:?} vs {} {:?}", hash, color, n, other);
+    }
+}
+
+#[test]
+fn test_neighbors_table() {
+    // Table lookups agree with computing each neighbor on its own
+    let mut hashes: Vec<String> = GridWalker::new("000", 64, 64).unwrap().collect();
+    assert_eq!(hashes.len(), 4096);
+    hashes.extend(["4", "e7", "E7"].iter().map(|s| s.to_string()));
+    for hash in hashes.iter() {
+        let expected = (|| -> Result<Neighbors, failure::Error> {
+            Ok(Neighbors {
+                sw: neighbor(hash, Direction::SW)?,
+                s: neighbor(hash, Direction::S)?,
+                se: neighbor(hash, Direction::SE)?,
+                w: neighbor(hash, Direction::W)?,
+                e: neighbor(hash, Direction::E)?,
+                nw: neighbor(hash, Direction::NW)?,
+                n: neighbor(hash, Direction::N)?,
+                ne: neighbor(hash, Direction::NE)?,
+            })
+        })();
+        match (neighbors(hash), expected) {
+            (Ok(found), Ok(expected)) => assert_eq!(found, expected, "{}", hash),
+            (Err(found), Err(expected)) => assert_eq!(found.to_string(), expected.to_string()),
+            (found, expected) => panic!("{}: {:?} vs {:?}", hash, found, expected),
+        }
+    }
+    assert_eq!(NEIGHBOR_TABLE_MAX_LEN, 3);
+    assert!(neighbors("e7z").is_err());
+}
+
+#[test]
+fn test_antipode() {
+    // Across the antimeridian in both directions and across the equator
+    assert_eq!(antipode("0").unwrap(), "d");
+    assert_eq!(antipode("f").unwrap(), "2");
+    assert_eq!(antipode("3").unwrap(), "e");
+    assert_eq!(antipode("6").unwrap(), "b");
+
+    for hash in ["e71150dc99", "4d8c0f1817", "0000", "ffff", "7fff"].iter() {
+        let opposite = antipode(hash).unwrap();
+        assert_eq!(opposite.len(), hash.len());
+        assert_eq!(antipode(&opposite).unwrap(), *hash);
+
+        let (c, _, _) = decode(hash).unwrap();
+        let (o, _, _) = decode(&opposite).unwrap();
+        assert_eq!(o.y, -c.y);
+        assert_eq!((o.x - c.x).abs(), 180f64);
+    }
+
+    assert_eq!(antipode("E71150").unwrap(), antipode("e71150").unwrap());
+    assert!(antipode("e7g").is_err());
+    assert!(antipode("").is_err());
+}
+
+#[test]
+fn test_decode_normalized() {
+    let hashes = ["e71150dc9", "e71150", "e7115", "e71150aa"];
+    let coords = decode_normalized(&hashes, 6usize).unwrap();
+    let (center, _, _) = decode("e71150").unwrap();
+    assert_eq!(coords.len(), 4);
+    assert_eq!(coords[0], center);
+    assert_eq!(coords[1], center);
+    assert_eq!(coords[3], center);
+    // The center of "e7115" is a corner of its length 6 children
+    let (parent, _, _) = decode("e7115").unwrap();
+    let (_, lon_err, lat_err) = decode("e71150").unwrap();
+    assert_eq!(coords[2].x, parent.x - lon_err);
+    assert_eq!(coords[2].y, parent.y - lat_err);
+
+    let err = decode_normalized(&["e71150", "e711z0"], 6usize).unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("invalid input at index 1: invalid hash character: z"));
+    match err.downcast_ref::<GeohashError>() {
+        Some(GeohashError::InvalidAtIndex { index: 1, .. }) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+    let err = decode_normalized(&["e71150"], 0usize).unwrap_err();
+    assert_eq!(err.to_string(), "invalid hash length: 0");
+}
+
+#[test]
+fn test_quadrants() {
+    let hash = "e7115";
+    let quads = quadrants(hash).unwrap();
+    let labels: Vec<Direction> = quads.iter().map(|&(direction, _)| direction).collect();
+    assert_eq!(
+        labels,
+        vec![Direction::NW, Direction::NE, Direction::SW, Direction::SE]
+    );
+
+    // Each quadrant holds exactly four of the 16 children
+    for &(_, rect) in quads.iter() {
+        let inner = rect.buffer(-1e-9, -1e-9);
+        let children = cover_bbox(&inner, 6usize).unwrap();
+        assert_eq!(children.len(), 4, "{:?}", children);
+        assert!(children.iter().all(|child| child.starts_with(hash)));
+    }
+    let bbox = decode_bbox(hash).unwrap();
+    assert_eq!(quads[0].1.min.x, bbox.min.x);
+    assert_eq!(quads[1].1.max, bbox.max);
+    assert_eq!(quads[2].1.min, bbox.min);
+    assert_eq!(quads[3].1.min.y, bbox.min.y);
+    assert!(quadrants("e711z").is_err());
+}
+
+#[test]
+fn test_validate_batch() {
+    let results = validate_batch(&["e71150", "", "E7115A", "e7!1", "e7éx", "e71g"]);
+    assert_eq!(results.len(), 6);
+    assert!(results[0].is_ok());
+    assert!(results[1].is_ok());
+    assert!(results[2].is_ok());
+    match &results[3] {
+        Err((2, GeohashError::InvalidHashCharacter { character: '!' })) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    // Positions count characters
+    match &results[4] {
+        Err((2, GeohashError::InvalidHashCharacter { character: 'é' })) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match &results[5] {
+        Err((3, GeohashError::Base32HashCharacter { character: 'g' })) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_neighbors_at_precision() {
+    let same = neighbors_at_precision("e71150dc99", 10).unwrap();
+    assert_eq!(same, neighbors("e71150dc99").unwrap());
+
+    // Coarser: each neighbor is truncated
+    let found = neighbors("e71150dc99").unwrap();
+    let coarse = neighbors_at_precision("e71150dc99", 9).unwrap();
+    assert_eq!(coarse.n, found.n[..9]);
+    assert_eq!(coarse.sw, found.sw[..9]);
+    let coarse = neighbors_at_precision("e71150dc99", 8).unwrap();
+    assert_eq!(coarse.e, "e71150dc");
+
+    // Finer: each neighbor is replaced by a child touching the input cell
+    let rect = decode_bbox("e71150").unwrap();
+    let found = neighbors("e71150").unwrap();
+    for out_len in 7..10 {
+        let fine = neighbors_at_precision("e71150", out_len).unwrap();
+        let pairs = [
+            (&fine.sw, &found.sw),
+            (&fine.s, &found.s),
+            (&fine.se, &found.se),
+            (&fine.w, &found.w),
+            (&fine.e, &found.e),
+            (&fine.nw, &found.nw),
+            (&fine.n, &found.n),
+            (&fine.ne, &found.ne),
+        ];
+        for &(child, parent) in pairs.iter() {
+            assert_eq!(child.len(), out_len);
+            assert!(
+                child.starts_with(parent.as_str()),
+                "{} in {}",
+                child,
+                parent
+            );
+        }
+        let ne = decode_bbox(&fine.ne).unwrap();
+        assert_eq!((ne.min.x, ne.min.y), (rect.max.x, rect.max.y));
+        let sw = decode_bbox(&fine.sw).unwrap();
+        assert_eq!((sw.max.x, sw.max.y), (rect.min.x, rect.min.y));
+        let n = decode_bbox(&fine.n).unwrap();
+        assert_eq!(n.min.y, rect.max.y);
+        assert_eq!(n.max.x, (rect.min.x + rect.max.x) / 2f64);
+        let w = decode_bbox(&fine.w).unwrap();
+        assert_eq!(w.max.x, rect.min.x);
+        assert_eq!(w.max.y, (rect.min.y + rect.max.y) / 2f64);
+    }
+
+    assert!(neighbors_at_precision("e71150", 0).is_err());
+    assert!(neighbors_at_precision("e71150", 17).is_err());
+    assert!(neighbors_at_precision("f", 2).is_err());
+    assert!(neighbors_at_precision("e7g", 2).is_err());
+}
+
+#[test]
+fn test_neighbors_all() {
+    let hashes = ["e71150dc9", "4d8c0f1817", "e71150dc9", "e7"];
+    let all = neighbors_all(&hashes).unwrap();
+    assert_eq!(all.len(), 4);
+    for (hash, found) in hashes.iter().zip(all.iter()) {
+        assert_eq!(*found, neighbors(hash).unwrap());
+    }
+    assert!(neighbors_all(&[]).unwrap().is_empty());
+
+    let err = neighbors_all(&["e71150", "e71150", "e7z"]).unwrap_err();
+    assert!(
+        err.to_string().starts_with("invalid input at index 2: "),
+        "{}",
+        err
+    );
+    match err.downcast_ref::<GeohashError>() {
+        Some(GeohashError::InvalidAtIndex { index: 2, .. }) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+    // Cells in the top row have no northern neighbors
+    assert!(neighbors_all(&["e71150", "5"]).is_err());
+}
+
+#[cfg(feature = "prost")]
+#[test]
+fn test_to_proto() {
+    use geohash::{to_proto, GeohashCell};
+    use prost::Message;
+
+    let cell = to_proto("E71150").unwrap();
+    let (center, _, _) = decode("e71150").unwrap();
+    let rect = decode_bbox("e71150").unwrap();
+    assert_eq!(cell.geohash, "e71150");
+    assert_eq!((cell.lon, cell.lat), (center.x, center.y));
+    assert_eq!((cell.min_lon, cell.min_lat), (rect.min.x, rect.min.y));
+    assert_eq!((cell.max_lon, cell.max_lat), (rect.max.x, rect.max.y));
+
+    let bytes = cell.encode_to_vec();
+    assert_eq!(GeohashCell::decode(&bytes[..]).unwrap(), cell);
+    // the geohash string leads the message as field 1
+    assert_eq!(&bytes[..8], b"\x0a\x06e71150");
+
+    assert!(to_proto("e7g").is_err());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_cover_bbox_parallel() {
+    use geohash::cover_bbox_parallel;
+
+    let rects = [
+        Rect {
+            min: Coordinate {
+                x: 112.13f64,
+                y: 37.61f64,
+            },
+            max: Coordinate {
+                x: 112.94f64,
+                y: 38.12f64,
+            },
+        },
+        decode_bbox("e7").unwrap(),
+        decode_bbox("").unwrap(),
+    ];
+    for rect in rects.iter() {
+        for len in 1..6 {
+            assert_eq!(
+                cover_bbox_parallel(rect, len).unwrap(),
+                cover_bbox(rect, len).unwrap()
+            );
+        }
+    }
+
+    // An inverted rect covers nothing
+    let inverted = Rect {
+        min: rects[0].max,
+        max: rects[0].min,
+    };
+    assert_eq!(
+        cover_bbox_parallel(&inverted, 4).unwrap(),
+        cover_bbox(&inverted, 4).unwrap()
+    );
+
+    assert!(cover_bbox_parallel(&rects[0], 17).is_err());
+}
+
+#[cfg(feature = "geojson")]
+#[test]
+fn test_cover_geojson() {
+    use geohash::cover_geojson;
+
+    let outer = "[[110, 35], [116, 35], [116, 41], [110, 41], [110, 35]]";
+    let hole = "[[112, 37], [114, 37], [114, 39], [112, 39], [112, 37]]";
+    let polygon = format!(r#"{{"type": "Polygon", "coordinates": [{}]}}"#, outer);
+    let with_hole = format!(
+        r#"{{"type": "Polygon", "coordinates": [{}, {}]}}"#,
+        outer, hole
+    );
+
+    let square = [
+        Coordinate {
+            x: 110f64,
+            y: 35f64,
+        },
+        Coordinate {
+            x: 116f64,
+            y: 35f64,
+        },
+        Coordinate {
+            x: 116f64,
+            y: 41f64,
+        },
+        Coordinate {
+            x: 110f64,
+            y: 41f64,
+        },
+    ];
+    let full = cover_geojson(&polygon, 4usize).unwrap();
+    assert_eq!(full, cover_polygon(&square, 4usize).unwrap());
+
+    // Cells strictly inside the hole are dropped, those on its edge kept
+    let holed = cover_geojson(&with_hole, 5usize).unwrap();
+    let in_hole = encode(
+        Coordinate {
+            x: 113f64,
+            y: 38f64,
+        },
+        5usize,
+    )
+    .unwrap();
+    let on_edge = encode(
+        Coordinate {
+            x: 112f64,
+            y: 38f64,
+        },
+        5usize,
+    )
+    .unwrap();
+    let solid = encode(
+        Coordinate {
+            x: 111f64,
+            y: 36f64,
+        },
+        5usize,
+    )
+    .unwrap();
+    assert!(!holed.contains(&in_hole));
+    assert!(holed.contains(&on_edge));
+    assert!(holed.contains(&solid));
+
+    let multi = format!(
+        r#"{{"type": "MultiPolygon", "coordinates": [[{}], [[[-10, -10], [-5, -10], [-5, -5], [-10, -10]]]]}}"#,
+        outer
+    );
+    let cells = cover_geojson(&multi, 4usize).unwrap();
+    assert!(full.iter().all(|cell| cells.contains(cell)));
+    assert!(cells.len() > full.len());
+
+    assert!(cover_geojson("{", 4usize).is_err());
+    let err = cover_geojson(r#"{"type": "Point", "coordinates": [1, 2]}"#, 4usize).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid GeoJSON geometry: unsupported geometry type Point"
+    );
+    assert!(cover_geojson(
+        r#"{"type": "Polygon", "coordinates": [[[1, "a"]]]}"#,
+        4usize
+    )
+    .is_err());
+}
+
+#[test]
+fn test_hash_range() {
+    let rect = Rect {
+        min: Coordinate {
+            x: 112.13f64,
+            y: 37.61f64,
+        },
+        max: Coordinate {
+            x: 113.37f64,
+            y: 38.29f64,
+        },
+    };
+    let (lo, hi) = hash_range(&rect, 5usize).unwrap();
+    let cells = cover_bbox(&rect, 5usize).unwrap();
+    assert_eq!(&lo, cells.iter().min().unwrap());
+    assert_eq!(&hi, cells.iter().max().unwrap());
+    // The range also holds cells outside the box
+    let span = u64::from_str_radix(&hi, 16).unwrap() - u64::from_str_radix(&lo, 16).unwrap() + 1;
+    assert!(span > cells.len() as u64);
+
+    let point = Rect {
+        min: rect.min,
+        max: rect.min,
+    };
+    let hash = encode(rect.min, 5usize).unwrap();
+    assert_eq!(hash_range(&point, 5usize).unwrap(), (hash.clone(), hash));
+    assert!(hash_range(&rect, 17usize).is_err());
+}
+
+#[test]
+fn test_neighbor_fn() {
+    let hash = "e71150dc9";
+    let find = neighbor_fn(hash).unwrap();
+    for &direction in Direction::ALL.iter() {
+        assert_eq!(find(direction).unwrap(), neighbor(hash, direction).unwrap());
+    }
+
+    let top = neighbor_fn("5").unwrap();
+    assert!(top(Direction::N).is_err());
+    assert_eq!(
+        top(Direction::S).unwrap(),
+        neighbor("5", Direction::S).unwrap()
+    );
+    assert!(neighbor_fn("e7z").is_err());
+}
+
+#[test]
+fn test_lat_lon_order() {
+    let hash = encode_lat_lon(32.01416f64, 117.02636f64, 8usize).unwrap();
+    let c = Coordinate {
+        x: 117.02636f64,
+        y: 32.01416f64,
+    };
+    assert_eq!(hash, encode(c, 8usize).unwrap());
+
+    let (lat, lon) = decode_lat_lon(&hash).unwrap();
+    let (center, _, _) = decode(&hash).unwrap();
+    assert_eq!((lat, lon), (center.y, center.x));
+
+    // A latitude beyond 90 is out of range, even though it would be a valid
+    // longitude
+    assert!(encode_lat_lon(117f64, 32f64, 8usize).is_err());
+    assert!(decode_lat_lon("e7z").is_err());
+}
+
+#[test]
+fn test_encode_iter() {
+    let coords = vec![
+        Coordinate {
+            x: 112.5584f64,
+            y: 37.8324f64,
+        },
+        Coordinate {
+            x: 190f64,
+            y: -100f64,
+        },
+        Coordinate {
+            x: -120.6623f64,
+            y: 35.3003f64,
+        },
+    ];
+    let hashes: Vec<Result<String, _>> = encode_iter(coords.clone(), 9).collect();
+    assert_eq!(hashes.len(), 3);
+    assert_eq!(hashes[0].as_ref().unwrap(), "e71150dc9");
+    assert!(hashes[1].is_err());
+    assert_eq!(hashes[2].as_ref().unwrap(), &encode(coords[2], 9).unwrap());
+
+    // Coordinates are only pulled as the hashes are consumed
+    let mut pulled = 0;
+    let mut lazy = encode_iter(
+        coords.iter().map(|&c| {
+            pulled += 1;
+            c
+        }),
+        4,
+    );
+    assert_eq!(lazy.next().unwrap().unwrap(), "e711");
+    drop(lazy);
+    assert_eq!(pulled, 1);
+
+    assert!(encode_iter(coords, 0).all(|hash| hash.is_err()));
+}
+
+#[test]
+fn test_is_stable() {
+    // Every cell of the first two levels, then blocks of deeper cells,
+    // including those along the world's edges
+    let mut hashes: Vec<String> = GridWalker::new("00", 16, 16).unwrap().collect();
+    for start in [
+        "0000000",
+        "e71150dc99",
+        "ffffff00",
+        "5555555555555",
+        "aaaaaaaaaaaaaaa0",
+    ]
+    .iter()
+    {
+        hashes.extend(GridWalker::new(start, 8, 8).unwrap());
+    }
+    hashes.extend(
+        ["0", "f", "ffffffffffffffff", "0000000000000000"]
+            .iter()
+            .map(|s| s.to_string()),
+    );
+    for hash in &hashes {
+        assert!(is_stable(hash).unwrap(), "{}", hash);
+    }
+
+    assert!(is_stable("e7g").is_err());
+    assert!(is_stable("").is_err());
+}
+
+#[test]
+fn test_hash_ranges() {
+    let rect = Rect {
+        min: Coordinate {
+            x: 112.13f64,
+            y: 37.61f64,
+        },
+        max: Coordinate {
+            x: 113.37f64,
+            y: 38.29f64,
+        },
+    };
+    let ranges = hash_ranges(&rect, 5usize).unwrap();
+    let mut cells = cover_bbox(&rect, 5usize).unwrap();
+    cells.sort();
+
+    // Expanding the ranges gives back exactly the cover, in order
+    let mut expanded = vec![];
+    for (lo, hi) in ranges.iter() {
+        let (lo, hi) = (
+            u64::from_str_radix(lo, 16).unwrap(),
+            u64::from_str_radix(hi, 16).unwrap(),
+        );
+        assert!(lo <= hi);
+        expanded.extend((lo..=hi).map(|value| format!("{:05x}", value)));
+    }
+    assert_eq!(expanded, cells);
+    // Ranges are as few as possible: none could be merged with the next
+    for pair in ranges.windows(2) {
+        let hi = u64::from_str_radix(&pair[0].1, 16).unwrap();
+        let lo = u64::from_str_radix(&pair[1].0, 16).unwrap();
+        assert!(lo > hi + 1);
+    }
+    assert!(ranges.len() < cells.len());
+
+    // A whole cell is a single range
+    let bbox = decode_bbox("e71").unwrap().buffer(-1e-9, -1e-9);
+    assert_eq!(
+        hash_ranges(&bbox, 5usize).unwrap(),
+        vec![("e7100".to_string(), "e71ff".to_string())]
+    );
+    assert!(hash_ranges(&rect, 0usize).is_err());
+    assert!(hash_ranges(&rect, 17usize).is_err());
+
+    // A large box at a fine length is walked along its edge only: millions
+    // of cells fit in a few thousand ranges
+    let large = Rect {
+        min: Coordinate {
+            x: 100f64,
+            y: 20f64,
+        },
+        max: Coordinate {
+            x: 110f64,
+            y: 30f64,
+        },
+    };
+    let ranges = hash_ranges(&large, 8usize).unwrap();
+    let total: u64 = ranges
+        .iter()
+        .map(|(lo, hi)| {
+            u64::from_str_radix(hi, 16).unwrap() - u64::from_str_radix(lo, 16).unwrap() + 1
+        })
+        .sum();
+    assert_eq!(total as usize, count_cells(&large, 8usize));
+    assert!(ranges.len() * 100 < total as usize);
+    let (first, last) = hash_range(&large, 8usize).unwrap();
+    assert_eq!(ranges[0].0, first);
+    assert_eq!(ranges[ranges.len() - 1].1, last);
+
+    // The whole world is one range
+    let world = Rect {
+        min: Coordinate {
+            x: -180f64,
+            y: -90f64,
+        },
+        max: Coordinate {
+            x: 180f64,
+            y: 90f64,
+        },
+    };
+    assert_eq!(
+        hash_ranges(&world, 16usize).unwrap(),
+        vec![("0".repeat(16), "f".repeat(16))]
+    );
+}
+
+#[test]
+fn test_geohash_index() {
+    let mut index = GeohashIndex::new();
+    assert!(index.is_empty());
+    let points = [
+        (112.5584f64, 37.8324f64, 6usize),
+        (112.5585f64, 37.8325f64, 6usize),
+        (112.9f64, 38.1f64, 4usize),
+        (113.5f64, 37.8f64, 6usize),
+        (117.02636f64, 32.01416f64, 8usize),
+    ];
+    for (i, &(x, y, len)) in points.iter().enumerate() {
+        index.insert(Coordinate { x, y }, len, i).unwrap();
+    }
+    assert_eq!(index.len(), 5);
+    assert!(index
+        .insert(Coordinate { x: 181f64, y: 0f64 }, 6usize, 9)
+        .is_err());
+    for &len in &[0usize, 17usize] {
+        let err = index
+            .insert(
+                Coordinate {
+                    x: 112.5f64,
+                    y: 37.8f64,
+                },
+                len,
+                9,
+            )
+            .unwrap_err();
+        assert_eq!(
+            format!("{}", err),
+            format!("{}", GeohashError::InvalidLength { len })
+        );
+    }
+    assert_eq!(index.len(), 5);
+
+    let rect = Rect {
+        min: Coordinate {
+            x: 112.5f64,
+            y: 37.5f64,
+        },
+        max: Coordinate {
+            x: 113.2f64,
+            y: 38.2f64,
+        },
+    };
+    let found = index.query_bbox(&rect).unwrap();
+    let mut values: Vec<usize> = found.iter().map(|&(_, &value)| value).collect();
+    values.sort();
+    assert_eq!(values, vec![0, 1, 2]);
+    for &(hash, &value) in found.iter() {
+        let (x, y, len) = points[value];
+        assert_eq!(hash, encode(Coordinate { x, y }, len).unwrap());
+    }
+
+    // Widening the box east picks up (113.5, 37.8)
+    let wide = Rect {
+        min: rect.min,
+        max: Coordinate {
+            x: 113.6f64,
+            y: 38.2f64,
+        },
+    };
+    assert_eq!(index.query_bbox(&wide).unwrap().len(), 4);
+    assert!(GeohashIndex::<usize>::new()
+        .query_bbox(&rect)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_geohash_map() {
+    let taiyuan = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    let mut map = GeohashMap::new(6);
+    assert!(map.is_empty());
+    assert_eq!(map.hash_len(), 6);
+    assert_eq!(map.insert(taiyuan, 1).unwrap(), None);
+    assert_eq!(map.len(), 1);
+
+    // Any point in the same cell finds the value, and replaces it
+    let (center, _, _) = decode(&encode(taiyuan, 6).unwrap()).unwrap();
+    assert_eq!(map.get(center).unwrap(), Some(&1));
+    assert_eq!(map.insert(center, 2).unwrap(), Some(1));
+    assert_eq!(map.len(), 1);
+
+    // Values in the neighboring cells, own cell first then clockwise
+    let hash = encode(taiyuan, 6).unwrap();
+    let around = neighbors(&hash).unwrap();
+    for (i, neighbor) in [&around.s, &around.ne, &around.nw].iter().enumerate() {
+        let (c, _, _) = decode(neighbor).unwrap();
+        map.insert(c, 10 + i).unwrap();
+    }
+    let far = decode("e72").unwrap().0;
+    map.insert(far, 99).unwrap();
+    assert_eq!(map.get(far).unwrap(), Some(&99));
+    assert_eq!(
+        map.get_with_neighbors(taiyuan).unwrap(),
+        vec![
+            (hash.clone(), &2),
+            (around.ne.clone(), &11),
+            (around.s.clone(), &10),
+            (around.nw.clone(), &12),
+        ]
+    );
+    let (empty, _, _) = decode(&around.e).unwrap();
+    assert_eq!(map.get(empty).unwrap(), None);
+
+    // Along the north pole row, missing neighbors are skipped
+    let mut top = GeohashMap::new(1);
+    top.insert(decode("5").unwrap().0, "5").unwrap();
+    top.insert(decode("4").unwrap().0, "4").unwrap();
+    assert_eq!(
+        top.get_with_neighbors(decode("5").unwrap().0).unwrap(),
+        vec![("5".to_string(), &"5"), ("4".to_string(), &"4")]
+    );
+
+    let invalid = Coordinate { x: 190f64, y: 0f64 };
+    assert!(map.insert(invalid, 0).is_err());
+    assert!(map.get(invalid).is_err());
+    assert!(map.get_with_neighbors(invalid).is_err());
+    assert!(GeohashMap::new(0).insert(taiyuan, 0).is_err());
+}
+
+#[test]
+fn test_top_level_neighbors() {
+    // The 16 top-level cells form a 4 x 4 grid; bits alternate longitude and
+    // latitude, so column and row each take every other bit of the value
+    let hash_at = |col: i32, row: i32| {
+        let value = (col >> 1) << 3 | (row >> 1) << 2 | (col & 1) << 1 | (row & 1);
+        format!("{:x}", value)
+    };
+    for col in 0..4 {
+        for row in 0..4 {
+            let hash = hash_at(col, row);
+            for &direction in Direction::ALL.iter() {
+                let (dlat, dlon) = direction.to_tuple();
+                let (c, r) = (col + dlon as i32, row + dlat as i32);
+                let found = neighbor(&hash, direction);
+                if (0..4).contains(&c) && (0..4).contains(&r) {
+                    assert_eq!(found.unwrap(), hash_at(c, r), "{} {:?}", hash, direction);
+                } else {
+                    // No wrapping over the poles or the antimeridian
+                    assert!(found.is_err(), "{} {:?}", hash, direction);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_axis_order() {
+    let coords = [
+        (112.5584f64, 37.8324f64),
+        (-120.6623f64, 35.3003f64),
+        (100f64, 10f64),
+        (-180f64, -90f64),
+        (180f64, 90f64),
+    ];
+    for &(x, y) in coords.iter() {
+        let c = Coordinate { x, y };
+        for len in 1..13 {
+            assert_eq!(
+                encode_with_axis_order(c, len, AxisOrder::LonFirst).unwrap(),
+                encode(c, len).unwrap()
+            );
+
+            // Each order decodes its own hashes to a cell holding the point
+            for &order in [AxisOrder::LonFirst, AxisOrder::LatFirst].iter() {
+                let hash = encode_with_axis_order(c, len, order).unwrap();
+                let (center, dx, dy) = decode_with_axis_order(&hash, order).unwrap();
+                assert!((center.x - x).abs() <= dx && (center.y - y).abs() <= dy);
+            }
+        }
+    }
+
+    // Latitude first is longitude first with the two bits of each pair
+    // swapped, so the hashes disagree whenever those bits do
+    assert_eq!(
+        encode_with_axis_order(
+            Coordinate {
+                x: 100f64,
+                y: 10f64
+            },
+            1,
+            AxisOrder::LatFirst
+        )
+        .unwrap(),
+        "d"
+    );
+    let c = Coordinate {
+        x: 112.5584f64,
+        y: 37.8324f64,
+    };
+    assert_ne!(
+        encode_with_axis_order(c, 8, AxisOrder::LatFirst).unwrap(),
+        encode(c, 8).unwrap()
+    );
+    let (lat_first, _, _) = decode_with_axis_order("e71150", AxisOrder::LatFirst).unwrap();
+    let (lon_first, _, _) = decode("e71150").unwrap();
+    assert_ne!(lat_first, lon_first);
+
+    let err = encode_with_axis_order(Coordinate { x: 190f64, y: 0f64 }, 4, AxisOrder::LatFirst)
+        .unwrap_err();
+    assert!(format!("{}", err).contains("x: 190"));
+    assert!(encode_with_axis_order(c, 0, AxisOrder::LatFirst).is_err());
+    assert!(decode_with_axis_order("e7g", AxisOrder::LatFirst).is_err());
+}
+
+#[test]
+fn test_encode_with_mode() {
+    // Away from boundaries both modes agree
+    for &(x, y) in [(112.5584f64, 37.8324f64), (-120.6623f64, 35.3003f64)].iter() {
+        let c = Coordinate { x, y };
+        for len in 1..10 {
+            let truncated = encode_with_mode(c, len, EncodeMode::Truncate).unwrap();
+            assert_eq!(truncated, encode(c, len).unwrap());
+            assert_eq!(
+                encode_with_mode(c, len, EncodeMode::Nearest).unwrap(),
+                truncated
+            );
+        }
+    }
+
+    // On whole degrees, Nearest splits boundary points between both sides
+    let mut truncate_west = 0;
+    let mut nearest_west = 0;
+    for lon in 60..92 {
+        let c = Coordinate {
+            x: f64::from(lon) * 1.40625,
+            y: 37.3f64,
+        };
+        let truncated = encode_with_mode(c, 4usize, EncodeMode::Truncate).unwrap();
+        let nearest = encode_with_mode(c, 4usize, EncodeMode::Nearest).unwrap();
+        let bbox = decode_bbox(&truncated).unwrap();
+        assert_eq!(bbox.max.x, c.x);
+        truncate_west += 1;
+        if nearest == truncated {
+            nearest_west += 1;
+        } else {
+            assert_eq!(nearest, neighbor(&truncated, Direction::E).unwrap());
+        }
+    }
+    assert_eq!(truncate_west, 32);
+    assert_eq!(nearest_west, 16);
+
+    // World edges stay in the only cell there is
+    let corner = Coordinate {
+        x: 180f64,
+        y: 90f64,
+    };
+    assert_eq!(
+        encode_with_mode(corner, 3usize, EncodeMode::Nearest).unwrap(),
+        encode(corner, 3usize).unwrap()
+    );
+    assert!(encode_with_mode(
+        Coordinate { x: 181f64, y: 0f64 },
+        3usize,
+        EncodeMode::Nearest
+    )
+    .is_err());
+}